@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use fzf_wrapped::{Fzf, FzfOutcome};
+
+#[tokio::main]
+async fn main() {
+    // Simulates results arriving slowly from an async api
+    let colours = futures::stream::iter(vec![
+        "red", "orange", "yellow", "green", "blue", "indigo", "violet",
+    ])
+    .then(|colour| async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        colour
+    });
+
+    let fzf = Fzf::builder()
+        .header("Colours are still loading...")
+        .build()
+        .unwrap();
+
+    let mut session = fzf.run_async().await.expect("Failed to start fzf");
+    // Returns once every colour has loaded, or as soon as the user selects one that already has
+    session
+        .add_stream(colours)
+        .await
+        .expect("Failed to add items");
+
+    match session.output().await {
+        Ok(FzfOutcome::Selected(colour)) => println!("{} is an awesome colour!", colour),
+        Ok(_) => println!("Maybe next time!"),
+        Err(e) => eprintln!("Failed to run fzf: {}", e),
+    }
+}
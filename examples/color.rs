@@ -1,6 +1,6 @@
 use fzf_wrapped::run_with_output;
-use fzf_wrapped::Fzf;
-use fzf_wrapped::{Border, Color, Layout};
+use fzf_wrapped::{Border, Color, Layout, Size};
+use fzf_wrapped::{Fzf, FzfOutcome};
 
 fn main() {
     let colours = vec![
@@ -14,13 +14,16 @@ fn main() {
         .color(Color::Bw)
         .header("Pick your favourite colour")
         .header_first(true)
-        .custom_args(vec!["--height=10".to_string()])
+        .height(Size::Lines(10))
         .build()
         .unwrap();
 
     let users_selection = run_with_output(fzf, colours);
 
-    if let Some(colour) = users_selection {
-        println!("{} is an awesome colour!", colour);
+    match users_selection {
+        Ok(FzfOutcome::Selected(colour)) => println!("{} is an awesome colour!", colour),
+        Ok(FzfOutcome::Aborted) => println!("Maybe next time!"),
+        Ok(_) => println!("That's not a colour!"),
+        Err(e) => eprintln!("Failed to run fzf: {}", e),
     }
 }
@@ -0,0 +1,483 @@
+//! This module contains the [`Action`] enum, which represents the actions `fzf` can bind to a
+//! [`Key`]
+
+use crate::error::FzfError;
+use crate::key::{self, Key};
+use crate::options::Multi;
+
+/// The pairs of delimiters `fzf` accepts around an action's payload, in order of preference
+const PAYLOAD_DELIMITERS: [(char, char); 16] = [
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('<', '>'),
+    ('~', '~'),
+    ('!', '!'),
+    ('@', '@'),
+    ('#', '#'),
+    ('$', '$'),
+    ('%', '%'),
+    ('^', '^'),
+    ('&', '&'),
+    ('*', '*'),
+    (';', ';'),
+    ('/', '/'),
+    ('|', '|'),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Enum to represent an action `fzf` can perform when a key is pressed or an event happens
+///
+/// Actions that take a command, such as `Execute` and `Reload`, are run by `fzf` with `$SHELL`,
+/// with placeholders like `{}` replaced by the focused item. Actions without a variant can be
+/// given with `Custom`
+pub enum Action {
+    Abort,
+    Accept,
+    AcceptNonEmpty,
+    AcceptOrPrintQuery,
+    BackwardChar,
+    BackwardDeleteChar,
+    /// Deletes the character before the cursor, aborting if the query is empty
+    BackwardDeleteCharEof,
+    BackwardKillWord,
+    BackwardWord,
+    BeginningOfLine,
+    /// Clears the query, aborting if it is already empty
+    Cancel,
+    ClearQuery,
+    ClearScreen,
+    ClearSelection,
+    DeleteChar,
+    /// Deletes the character under the cursor, aborting if the query is empty
+    DeleteCharEof,
+    Deselect,
+    DeselectAll,
+    DisableSearch,
+    Down,
+    EnableSearch,
+    EndOfLine,
+    First,
+    ForwardChar,
+    ForwardWord,
+    HalfPageDown,
+    HalfPageUp,
+    HideHeader,
+    HidePreview,
+    Ignore,
+    Jump,
+    KillLine,
+    KillWord,
+    Last,
+    NextHistory,
+    NextSelected,
+    /// Scrolls the list up by one line, without moving the cursor
+    OffsetUp,
+    /// Scrolls the list down by one line, without moving the cursor
+    OffsetDown,
+    PageDown,
+    PageUp,
+    PrevHistory,
+    PrevSelected,
+    PreviewBottom,
+    PreviewDown,
+    PreviewHalfPageDown,
+    PreviewHalfPageUp,
+    PreviewPageDown,
+    PreviewPageUp,
+    PreviewTop,
+    PreviewUp,
+    PrintQuery,
+    RefreshPreview,
+    Select,
+    SelectAll,
+    ShowHeader,
+    ShowPreview,
+    Toggle,
+    ToggleAll,
+    ToggleDown,
+    ToggleHeader,
+    ToggleIn,
+    ToggleOut,
+    TogglePreview,
+    TogglePreviewWrap,
+    ToggleSearch,
+    ToggleSort,
+    ToggleTrack,
+    ToggleUp,
+    TrackCurrent,
+    UnixLineDiscard,
+    UnixWordRubout,
+    Up,
+    Yank,
+    /// Replaces `fzf` with the given command
+    Become(String),
+    ChangeBorderLabel(String),
+    ChangeHeader(String),
+    /// Changes how many items can be selected, e.g. `Action::ChangeMulti(Multi::Disabled)`
+    ChangeMulti(Multi),
+    ChangePreview(String),
+    ChangePreviewLabel(String),
+    /// Changes the preview window, e.g. `Action::ChangePreviewWindow("down,50%".to_string())`
+    ChangePreviewWindow(String),
+    ChangePrompt(String),
+    ChangeQuery(String),
+    /// Runs the given command, handing the terminal over to it until it exits
+    Execute(String),
+    /// Runs the given command in the background
+    ExecuteSilent(String),
+    /// Moves the cursor to the given position, counting from the end if negative
+    Pos(i32),
+    /// Shows the output of the given command in the preview window
+    Preview(String),
+    /// Adds the given text to the output printed when `fzf` exits
+    Print(String),
+    /// Inserts the given text into the query
+    Put(String),
+    /// Re-enables the bindings of the given keys
+    Rebind(Vec<Key>),
+    /// Replaces the items with the output of the given command
+    Reload(String),
+    /// Like `Reload`, but keeps the current items until the command has finished
+    ReloadSync(String),
+    /// Runs the given command, performing the actions it prints
+    Transform(String),
+    TransformBorderLabel(String),
+    TransformHeader(String),
+    TransformPreviewLabel(String),
+    TransformPrompt(String),
+    TransformQuery(String),
+    /// Disables the bindings of the given keys
+    Unbind(Vec<Key>),
+    /// Any other action, passed to `fzf` as it is, e.g. `Action::Custom("offset-middle".into())`
+    ///
+    /// Payloads aren't wrapped in delimiters, so they must be included
+    Custom(String),
+}
+
+/// Gets the closing delimiter of a payload opened with the given character, if it opens one
+pub(crate) fn closing_delimiter(open: char) -> Option<char> {
+    PAYLOAD_DELIMITERS
+        .iter()
+        .find(|(x, _)| *x == open)
+        .map(|(_, close)| *close)
+}
+
+impl Action {
+    /// Gets the name `fzf` uses for the action
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Abort => "abort",
+            Self::Accept => "accept",
+            Self::AcceptNonEmpty => "accept-non-empty",
+            Self::AcceptOrPrintQuery => "accept-or-print-query",
+            Self::BackwardChar => "backward-char",
+            Self::BackwardDeleteChar => "backward-delete-char",
+            Self::BackwardDeleteCharEof => "backward-delete-char/eof",
+            Self::BackwardKillWord => "backward-kill-word",
+            Self::BackwardWord => "backward-word",
+            Self::BeginningOfLine => "beginning-of-line",
+            Self::Cancel => "cancel",
+            Self::ClearQuery => "clear-query",
+            Self::ClearScreen => "clear-screen",
+            Self::ClearSelection => "clear-selection",
+            Self::DeleteChar => "delete-char",
+            Self::DeleteCharEof => "delete-char/eof",
+            Self::Deselect => "deselect",
+            Self::DeselectAll => "deselect-all",
+            Self::DisableSearch => "disable-search",
+            Self::Down => "down",
+            Self::EnableSearch => "enable-search",
+            Self::EndOfLine => "end-of-line",
+            Self::First => "first",
+            Self::ForwardChar => "forward-char",
+            Self::ForwardWord => "forward-word",
+            Self::HalfPageDown => "half-page-down",
+            Self::HalfPageUp => "half-page-up",
+            Self::HideHeader => "hide-header",
+            Self::HidePreview => "hide-preview",
+            Self::Ignore => "ignore",
+            Self::Jump => "jump",
+            Self::KillLine => "kill-line",
+            Self::KillWord => "kill-word",
+            Self::Last => "last",
+            Self::NextHistory => "next-history",
+            Self::NextSelected => "next-selected",
+            Self::OffsetUp => "offset-up",
+            Self::OffsetDown => "offset-down",
+            Self::PageDown => "page-down",
+            Self::PageUp => "page-up",
+            Self::PrevHistory => "prev-history",
+            Self::PrevSelected => "prev-selected",
+            Self::PreviewBottom => "preview-bottom",
+            Self::PreviewDown => "preview-down",
+            Self::PreviewHalfPageDown => "preview-half-page-down",
+            Self::PreviewHalfPageUp => "preview-half-page-up",
+            Self::PreviewPageDown => "preview-page-down",
+            Self::PreviewPageUp => "preview-page-up",
+            Self::PreviewTop => "preview-top",
+            Self::PreviewUp => "preview-up",
+            Self::PrintQuery => "print-query",
+            Self::RefreshPreview => "refresh-preview",
+            Self::Select => "select",
+            Self::SelectAll => "select-all",
+            Self::ShowHeader => "show-header",
+            Self::ShowPreview => "show-preview",
+            Self::Toggle => "toggle",
+            Self::ToggleAll => "toggle-all",
+            Self::ToggleDown => "toggle+down",
+            Self::ToggleHeader => "toggle-header",
+            Self::ToggleIn => "toggle-in",
+            Self::ToggleOut => "toggle-out",
+            Self::TogglePreview => "toggle-preview",
+            Self::TogglePreviewWrap => "toggle-preview-wrap",
+            Self::ToggleSearch => "toggle-search",
+            Self::ToggleSort => "toggle-sort",
+            Self::ToggleTrack => "toggle-track",
+            Self::ToggleUp => "toggle+up",
+            Self::TrackCurrent => "track-current",
+            Self::UnixLineDiscard => "unix-line-discard",
+            Self::UnixWordRubout => "unix-word-rubout",
+            Self::Up => "up",
+            Self::Yank => "yank",
+            Self::Become(_) => "become",
+            Self::ChangeBorderLabel(_) => "change-border-label",
+            Self::ChangeHeader(_) => "change-header",
+            Self::ChangeMulti(_) => "change-multi",
+            Self::ChangePreview(_) => "change-preview",
+            Self::ChangePreviewLabel(_) => "change-preview-label",
+            Self::ChangePreviewWindow(_) => "change-preview-window",
+            Self::ChangePrompt(_) => "change-prompt",
+            Self::ChangeQuery(_) => "change-query",
+            Self::Execute(_) => "execute",
+            Self::ExecuteSilent(_) => "execute-silent",
+            Self::Pos(_) => "pos",
+            Self::Preview(_) => "preview",
+            Self::Print(_) => "print",
+            Self::Put(_) => "put",
+            Self::Rebind(_) => "rebind",
+            Self::Reload(_) => "reload",
+            Self::ReloadSync(_) => "reload-sync",
+            Self::Transform(_) => "transform",
+            Self::TransformBorderLabel(_) => "transform-border-label",
+            Self::TransformHeader(_) => "transform-header",
+            Self::TransformPreviewLabel(_) => "transform-preview-label",
+            Self::TransformPrompt(_) => "transform-prompt",
+            Self::TransformQuery(_) => "transform-query",
+            Self::Unbind(_) => "unbind",
+            Self::Custom(_) => "custom",
+        }
+    }
+
+    /// Gets the payload of the action, if it takes one
+    fn payload(&self) -> Option<String> {
+        match self {
+            Self::Become(x)
+            | Self::ChangeBorderLabel(x)
+            | Self::ChangeHeader(x)
+            | Self::ChangePreview(x)
+            | Self::ChangePreviewLabel(x)
+            | Self::ChangePreviewWindow(x)
+            | Self::ChangePrompt(x)
+            | Self::ChangeQuery(x)
+            | Self::Execute(x)
+            | Self::ExecuteSilent(x)
+            | Self::Preview(x)
+            | Self::Print(x)
+            | Self::Put(x)
+            | Self::Reload(x)
+            | Self::ReloadSync(x)
+            | Self::Transform(x)
+            | Self::TransformBorderLabel(x)
+            | Self::TransformHeader(x)
+            | Self::TransformPreviewLabel(x)
+            | Self::TransformPrompt(x)
+            | Self::TransformQuery(x) => Some(x.clone()),
+            Self::Pos(position) => Some(position.to_string()),
+            Self::ChangeMulti(Multi::Disabled) => Some("0".to_string()),
+            Self::ChangeMulti(Multi::Limit(limit)) => Some(limit.to_string()),
+            Self::Rebind(keys) | Self::Unbind(keys) => Some(key::key_list(keys)),
+            _ => None,
+        }
+    }
+
+    /// Creates the text `fzf` parses the action from
+    ///
+    /// The payload is wrapped in the first pair of delimiters it doesn't contain. If it contains
+    /// all of them, the `action:payload` form is used, which `fzf` only allows for the last action
+    fn render(&self, last: bool) -> Result<String, FzfError> {
+        if let Self::Custom(action) = self {
+            return Ok(action.clone());
+        }
+        if let Self::Rebind(keys) | Self::Unbind(keys) = self {
+            for key in keys {
+                key.validate()?;
+            }
+        }
+        let Some(payload) = self.payload() else {
+            return Ok(self.name().to_string());
+        };
+        let delimiters = PAYLOAD_DELIMITERS
+            .iter()
+            .find(|(_, close)| !payload.contains(*close));
+        match delimiters {
+            Some((open, close)) => Ok(format!("{}{}{}{}", self.name(), open, payload, close)),
+            None if last => Ok(format!("{}:{}", self.name(), payload)),
+            None => Err(FzfError::InvalidBind(format!(
+                "the payload of {} contains every delimiter, so it must be the last action",
+                self.name()
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A key bound to a chain of actions, created by [`crate::FzfBuilder::bind`]
+pub(crate) struct Binding {
+    pub(crate) key: Key,
+    pub(crate) actions: Vec<Action>,
+}
+
+impl Binding {
+    /// Creates the `--bind` argument for the binding
+    pub(crate) fn to_arg(&self) -> Result<String, FzfError> {
+        if self.actions.is_empty() {
+            return Err(FzfError::InvalidBind(format!(
+                "no actions bound to {}",
+                self.key
+            )));
+        }
+        Ok(format!("--bind={}:{}", self.key, chain(&self.actions)?))
+    }
+}
+
+/// Merges the bindings this crate needs into the configured ones, so there's one binding for each
+/// key, as `fzf` only keeps the last `--bind` of each key
+///
+/// The needed actions run first, followed by the last chain the key was configured with
+pub(crate) fn merge(needed: Vec<Binding>, configured: &[Binding]) -> Vec<Binding> {
+    let mut merged: Vec<Binding> = vec![];
+    for binding in needed {
+        match merged.iter_mut().find(|x| x.key == binding.key) {
+            Some(existing) => existing.actions.extend(binding.actions),
+            None => merged.push(binding),
+        }
+    }
+    for (i, binding) in configured.iter().enumerate() {
+        if configured[i + 1..].iter().any(|x| x.key == binding.key) {
+            continue;
+        }
+        match merged.iter_mut().find(|x| x.key == binding.key) {
+            Some(existing) => existing.actions.extend(binding.actions.iter().cloned()),
+            None => merged.push(binding.clone()),
+        }
+    }
+    merged
+}
+
+/// Joins the actions into a chain `fzf` runs one after another, such as `first+last`
+pub(crate) fn chain(actions: &[Action]) -> Result<String, FzfError> {
+    let actions = actions
+        .iter()
+        .enumerate()
+        .map(|(i, action)| action.render(i == actions.len() - 1))
+        .collect::<Result<Vec<String>, FzfError>>()?;
+    Ok(actions.join("+"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arg(key: Key, actions: Vec<Action>) -> Result<String, FzfError> {
+        Binding { key, actions }.to_arg()
+    }
+
+    #[test]
+    fn needed_bindings_are_merged_into_configured_ones() {
+        let binding = |key, actions| Binding { key, actions };
+        let needed = vec![
+            binding(Key::Start, vec![Action::ToggleAll]),
+            binding(Key::Start, vec![Action::First]),
+        ];
+        let configured = [
+            binding(Key::Ctrl('a'), vec![Action::SelectAll]),
+            binding(Key::Start, vec![Action::Last]),
+            binding(Key::Start, vec![Action::Accept]),
+        ];
+
+        assert_eq!(
+            merge(needed, &configured),
+            vec![
+                binding(
+                    Key::Start,
+                    vec![Action::ToggleAll, Action::First, Action::Accept]
+                ),
+                binding(Key::Ctrl('a'), vec![Action::SelectAll]),
+            ]
+        );
+    }
+
+    #[test]
+    fn actions_are_chained() {
+        assert_eq!(
+            arg(Key::Ctrl('a'), vec![Action::SelectAll, Action::Accept]).unwrap(),
+            "--bind=ctrl-a:select-all+accept"
+        );
+        assert_eq!(
+            arg(
+                Key::Change,
+                vec![Action::Unbind(vec![Key::Change, Key::Enter])]
+            )
+            .unwrap(),
+            "--bind=change:unbind(change,enter)"
+        );
+        assert!(arg(Key::Enter, vec![]).is_err());
+    }
+
+    #[test]
+    fn multi_can_be_changed() {
+        let change = |multi| arg(Key::Ctrl('t'), vec![Action::ChangeMulti(multi)]).unwrap();
+
+        assert_eq!(change(Multi::Unlimited), "--bind=ctrl-t:change-multi");
+        assert_eq!(change(Multi::Limit(3)), "--bind=ctrl-t:change-multi(3)");
+        assert_eq!(change(Multi::Disabled), "--bind=ctrl-t:change-multi(0)");
+    }
+
+    #[test]
+    fn custom_actions_are_passed_as_they_are() {
+        let actions = vec![
+            Action::OffsetUp,
+            Action::Custom("change-nth(2..)".to_string()),
+        ];
+        assert_eq!(
+            arg(Key::Ctrl('y'), actions).unwrap(),
+            "--bind=ctrl-y:offset-up+change-nth(2..)"
+        );
+    }
+
+    #[test]
+    fn payloads_are_wrapped_in_an_unused_delimiter() {
+        let execute = |x: &str| Action::Execute(x.to_string());
+
+        assert_eq!(
+            arg(Key::F(2), vec![execute("vim {}"), Action::Abort]).unwrap(),
+            "--bind=f2:execute(vim {})+abort"
+        );
+        assert_eq!(
+            arg(Key::F(2), vec![execute("echo (a+b)")]).unwrap(),
+            "--bind=f2:execute[echo (a+b)]"
+        );
+        assert_eq!(
+            arg(Key::Enter, vec![execute("echo ()[]{}<>")]).unwrap(),
+            "--bind=enter:execute~echo ()[]{}<>~"
+        );
+
+        let everything: String = PAYLOAD_DELIMITERS.iter().map(|(_, close)| close).collect();
+        assert_eq!(
+            arg(Key::Enter, vec![execute(&everything)]).unwrap(),
+            format!("--bind=enter:execute:{}", everything)
+        );
+        assert!(arg(Key::Enter, vec![execute(&everything), Action::Abort]).is_err());
+    }
+}
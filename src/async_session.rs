@@ -0,0 +1,432 @@
+//! This module contains the [`AsyncFzfSession`] struct, which runs `fzf` with `tokio::process`
+
+use std::{future, pin::pin};
+
+use futures_core::Stream;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    process::{Child, ChildStderr, ChildStdin},
+    task::JoinHandle,
+};
+
+use crate::callback::CallbackServer;
+use crate::error::FzfError;
+#[cfg(feature = "listen")]
+use crate::listen::FzfClient;
+use crate::outcome::{Accepted, FzfExit, FzfOutcome, Selection};
+use crate::session::{ItemTracker, OutputFormat, StderrCapture};
+
+#[derive(Debug)]
+/// Struct that represents a running instance of `fzf`, created by [`crate::Fzf::run_async`]
+///
+/// This is the async counterpart of [`crate::FzfSession`], which never blocks the executor while
+/// adding items or waiting for output
+pub struct AsyncFzfSession {
+    /// Contains the child stuct returned when the fzf command is spawned
+    instance: Child,
+    /// Contains the child process stdin handle, until it is closed
+    stdin: Option<ChildStdin>,
+    /// Contains the task forwarding the child process stderr, which returns the captured stderr
+    /// once `fzf` exits
+    stderr: Option<JoinHandle<String>>,
+    /// Warnings about options that weren't passed to `fzf`, because of the version policy
+    warnings: Vec<String>,
+    /// The server answering callbacks from the commands `fzf` runs, which is stopped once `fzf`
+    /// exits
+    callbacks: Option<CallbackServer>,
+    /// The client for the http server `fzf` starts with the `listen` option
+    #[cfg(feature = "listen")]
+    client: Option<FzfClient>,
+    /// Keeps track of the items added, for parsing `fzf`'s output
+    tracker: ItemTracker,
+}
+
+impl AsyncFzfSession {
+    /// Creates a session for the given `fzf` child process
+    ///
+    /// Must be called from within a tokio runtime, as a task is spawned to forward stderr
+    pub(crate) fn new(
+        mut instance: Child,
+        format: OutputFormat,
+        warnings: Vec<String>,
+        callbacks: Option<CallbackServer>,
+    ) -> Self {
+        Self {
+            stdin: instance.stdin.take(),
+            stderr: instance.stderr.take().map(forward_stderr),
+            instance,
+            warnings,
+            callbacks,
+            #[cfg(feature = "listen")]
+            client: None,
+            tracker: ItemTracker::new(format),
+        }
+    }
+
+    /// Closes `fzf`'s stdin, letting it know that no more items will be added
+    pub fn close_stdin(&mut self) {
+        drop(self.stdin.take());
+    }
+
+    /// Adds an item to the `fzf` selection ui
+    ///
+    /// # Parameters
+    ///
+    /// - `item` The item to add
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::StdinClosed`] if stdin has already been closed, or
+    /// [`FzfError::BrokenPipe`] if `fzf` has stopped reading items
+    pub async fn add_item<T: Into<String>>(&mut self, item: T) -> Result<(), FzfError> {
+        let stdin = self.stdin.as_mut().ok_or(FzfError::StdinClosed)?;
+        write_item(stdin, &mut self.tracker, item.into()).await
+    }
+
+    /// Adds all the items in the given iterator to the `fzf` selection ui
+    ///
+    /// Like [`crate::FzfSession::add_items`], the remaining items are skipped if `fzf` stops
+    /// reading them
+    ///
+    /// # Parameters
+    ///
+    /// - `items` The items to add
+    ///
+    /// # Returns
+    ///
+    /// The number of items that were added before `fzf` stopped reading them
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `add_item`, other than [`FzfError::BrokenPipe`]
+    pub async fn add_items(
+        &mut self,
+        items: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<usize, FzfError> {
+        let mut added = 0;
+        for item in items.into_iter() {
+            match self.add_item(item).await {
+                Ok(()) => added += 1,
+                Err(FzfError::BrokenPipe) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(added)
+    }
+
+    /// Adds every item produced by the given stream to the `fzf` selection ui, as they arrive
+    ///
+    /// The stream stops being polled once `fzf` exits or stops reading items, so this returns as
+    /// soon as the user makes their selection, even if the stream hasn't ended
+    ///
+    /// # Parameters
+    ///
+    /// - `items` The stream of items to add
+    ///
+    /// # Returns
+    ///
+    /// The number of items that were added before `fzf` stopped reading them
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `add_item`, other than [`FzfError::BrokenPipe`]
+    pub async fn add_stream(
+        &mut self,
+        items: impl Stream<Item = impl Into<String>>,
+    ) -> Result<usize, FzfError> {
+        let stdin = self.stdin.as_mut().ok_or(FzfError::StdinClosed)?;
+        let mut items = pin!(items);
+        let mut added = 0;
+        let feeding = async {
+            while let Some(item) = future::poll_fn(|cx| items.as_mut().poll_next(cx)).await {
+                match write_item(stdin, &mut self.tracker, item.into()).await {
+                    Ok(()) => added += 1,
+                    Err(FzfError::BrokenPipe) => break,
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        };
+        // The stream may wait a long time for its next item, so it's raced against `fzf` exiting
+        let fed = tokio::select! {
+            fed = feeding => fed,
+            _ = self.instance.wait() => Ok(()),
+        };
+        fed.map(|()| added)
+    }
+
+    /// Gets the warnings about options that weren't passed to `fzf`, because the installed version
+    /// doesn't support them
+    ///
+    /// Only options dropped by [`crate::VersionPolicy::Warn`] are reported
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Sets the client for the http server `fzf` starts with the `listen` option
+    #[cfg(feature = "listen")]
+    pub(crate) fn with_client(mut self, client: Option<FzfClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Gets a client for the http server `fzf` starts with the `listen` option, which can send
+    /// actions to `fzf` and read its state while the user is making their selection
+    ///
+    /// **NOTE: The client's requests block, so they should be sent with
+    /// `tokio::task::spawn_blocking`**
+    ///
+    /// # Returns
+    ///
+    /// `None` if the `listen` option wasn't set
+    #[cfg(feature = "listen")]
+    pub fn client(&self) -> Option<FzfClient> {
+        self.client.clone()
+    }
+
+    /// Gets the number of items that have been sent to `fzf` so far
+    pub fn items_sent(&self) -> usize {
+        self.tracker.items_sent()
+    }
+
+    /// Waits for `fzf` to exit, returning its exit status, stdout, captured stderr and whether it
+    /// showed its ui
+    async fn wait(&mut self) -> Result<FzfExit, FzfError> {
+        drop(self.stdin.take());
+        let mut stdout = vec![];
+        if let Some(mut handle) = self.instance.stdout.take() {
+            handle
+                .read_to_end(&mut stdout)
+                .await
+                .map_err(FzfError::Wait)?;
+        }
+        let status = self.instance.wait().await.map_err(FzfError::Wait)?;
+        drop(self.callbacks.take());
+        let stderr = match self.stderr.take() {
+            Some(handle) => handle.await.unwrap_or_default(),
+            None => String::new(),
+        };
+        Ok(FzfExit {
+            status,
+            stdout,
+            stderr,
+            interacted: self.tracker.interacted(),
+        })
+    }
+
+    /// Gets the output of `fzf`, once the user has made their selection
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing the users selection as a [`String`] if they selected one
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`crate::FzfSession::output`]
+    pub async fn output(mut self) -> Result<FzfOutcome<String>, FzfError> {
+        let exit = self.wait().await?;
+        FzfOutcome::from_exit(exit, |stdout| self.tracker.selection(stdout))
+    }
+
+    /// Gets the output of `fzf` when multi-select is enabled, with each selected item as its own
+    /// entry.
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing the users selections as a [`Vec`] of [`String`]'s if they
+    /// selected any
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`crate::FzfSession::output`]
+    pub async fn output_multi(mut self) -> Result<FzfOutcome<Vec<String>>, FzfError> {
+        let exit = self.wait().await?;
+        FzfOutcome::from_exit(exit, |stdout| self.tracker.selections(stdout))
+    }
+
+    /// Gets the output of `fzf`, with the selected item split into its fields by the `delimiter`
+    /// option, or by whitespace if it wasn't set
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing the fields of the users selection if they selected one
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`crate::FzfSession::output`]
+    pub async fn output_fields(mut self) -> Result<FzfOutcome<Vec<String>>, FzfError> {
+        let exit = self.wait().await?;
+        FzfOutcome::from_exit(exit, |stdout| {
+            Ok(self.tracker.fields(&self.tracker.selection(stdout)?))
+        })
+    }
+
+    /// Gets the output of `fzf` when multi-select is enabled, with each selected item split into
+    /// its fields like the `output_fields` method
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing the fields of each of the users selections if they selected
+    /// any
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`crate::FzfSession::output`]
+    pub async fn output_fields_multi(mut self) -> Result<FzfOutcome<Vec<Vec<String>>>, FzfError> {
+        let exit = self.wait().await?;
+        FzfOutcome::from_exit(exit, |stdout| {
+            let selections = self.tracker.selections(stdout)?;
+            Ok(selections.iter().map(|x| self.tracker.fields(x)).collect())
+        })
+    }
+
+    /// Gets everything `fzf` printed when the user made their selection, including which key
+    /// they accepted with when using the `expect` option
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing a [`Selection`] if the user selected any items
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`crate::FzfSession::output`]
+    pub async fn output_selection(mut self) -> Result<FzfOutcome<Selection>, FzfError> {
+        let exit = self.wait().await?;
+        FzfOutcome::from_exit(exit, |stdout| self.tracker.parse(stdout))
+    }
+
+    /// Gets whether the user accepted an item, or their query because nothing matched it
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing what the user [`Accepted`]
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`crate::FzfSession::output`]
+    pub async fn output_accepted(self) -> Result<FzfOutcome<Accepted>, FzfError> {
+        Ok(self.output_selection().await?.map(Selection::accepted))
+    }
+}
+
+/// Writes a single item to `fzf`'s stdin, keeping track of it for parsing the output
+async fn write_item(
+    stdin: &mut ChildStdin,
+    tracker: &mut ItemTracker,
+    item: String,
+) -> Result<(), FzfError> {
+    let line = tracker.line(item);
+    stdin
+        .write_all(line.as_bytes())
+        .await
+        .map_err(FzfError::write)?;
+    tracker.sent();
+    Ok(())
+}
+
+/// Spawns a task that forwards `fzf`'s stderr to our own, while capturing it for error reporting
+fn forward_stderr(mut stderr: ChildStderr) -> JoinHandle<String> {
+    tokio::spawn(async move {
+        let mut capture = StderrCapture::default();
+        let mut forwarded = tokio::io::stderr();
+        let mut buffer = [0; 4096];
+        while let Ok(read @ 1..) = stderr.read(&mut buffer).await {
+            let _ = forwarded.write_all(&buffer[..read]).await;
+            capture.capture(&buffer[..read]);
+        }
+        capture.finish()
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::executable::{self, StandIn};
+    use crate::version::VersionPolicy;
+    use crate::Fzf;
+
+    use super::*;
+
+    /// Runs a stand-in for `fzf` that selects the first item, either once every item has been
+    /// read, or straight away without reading any others
+    ///
+    /// The stand-in is returned too, as it has to outlive the session
+    async fn run_first_line(wait_for_items: bool) -> (StandIn, AsyncFzfSession) {
+        let (name, script) = match wait_for_items {
+            true => ("first-line", "IFS= read -r x; cat >/dev/null; echo \"$x\""),
+            false => ("first-line-early", "IFS= read -r x; echo \"$x\""),
+        };
+        let stand_in = executable::stand_in(name, script);
+        let session = Fzf::builder()
+            .executable(stand_in.path())
+            .build()
+            .unwrap()
+            .run_async()
+            .await
+            .unwrap();
+        (stand_in, session)
+    }
+
+    #[tokio::test]
+    async fn options_are_checked_against_the_version() {
+        let script = "[ \"$1\" = --version ] && echo '0.24.0 (brew)' && exit; echo \"$*\"";
+        let stand_in = executable::stand_in("old-version", script);
+        let session = Fzf::builder()
+            .executable(stand_in.path())
+            .version_policy(VersionPolicy::Warn)
+            .disabled(true)
+            .build()
+            .unwrap()
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(session.warnings().len(), 1);
+        assert!(session.warnings()[0].starts_with("--disabled requires fzf 0.25.0"));
+        let output = session.output().await.unwrap();
+        assert!(output.selected().is_some_and(|x| !x.contains("--disabled")));
+    }
+
+    #[tokio::test]
+    async fn items_are_added_before_the_output_is_read() {
+        let (_stand_in, mut session) = run_first_line(true).await;
+
+        assert_eq!(session.add_items(["red", "green"]).await.unwrap(), 2);
+        assert_eq!(session.items_sent(), 2);
+        assert_eq!(
+            session.output().await.unwrap(),
+            FzfOutcome::Selected("red".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_stop_once_fzf_stops_reading() {
+        let (_stand_in, mut session) = run_first_line(false).await;
+
+        let items = futures::stream::iter((0..).map(|x: u64| x.to_string()));
+        let added = session.add_stream(items).await.unwrap();
+        assert_eq!(added, session.items_sent());
+        assert_eq!(
+            session.output().await.unwrap(),
+            FzfOutcome::Selected("0".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_waiting_for_items_stop_once_fzf_exits() {
+        let stand_in = executable::stand_in("no-read", "echo red");
+        let mut session = Fzf::builder()
+            .executable(stand_in.path())
+            .build()
+            .unwrap()
+            .run_async()
+            .await
+            .unwrap();
+
+        let added = session.add_stream(futures::stream::pending::<String>());
+        assert_eq!(added.await.unwrap(), 0);
+        assert_eq!(
+            session.output().await.unwrap(),
+            FzfOutcome::Selected("red".to_string())
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! This module contains the callback server, which lets commands run by `fzf`, such as the
+//! preview command, call back into rust closures running in this process
+//!
+//! `fzf` runs the current executable again with [`CALLBACK_ARG`] as its first argument, which
+//! [`handle_self_invocation`] spots. It then connects to the server over localhost, sends the
+//! request, and prints the response for `fzf` to read
+
+use std::{
+    collections::hash_map::RandomState,
+    env,
+    ffi::OsStr,
+    fmt,
+    hash::{BuildHasher, Hasher},
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    process,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use crate::error::FzfError;
+use crate::fzf::shell_quote;
+
+/// The first argument `fzf` passes when running the current executable for a callback
+pub(crate) const CALLBACK_ARG: &str = "__fzf_wrapped_callback";
+/// The environment variable holding the address of the callback server
+const ADDR_ENV: &str = "FZF_WRAPPED_CALLBACK_ADDR";
+/// The environment variable holding the token the callback server expects
+const TOKEN_ENV: &str = "FZF_WRAPPED_CALLBACK_TOKEN";
+/// How long the callback server waits before accepting connections again after failing to
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(10);
+/// How long a connection has to send its request, or read its response
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone)]
+/// A rust closure that a command run by `fzf` can call
+pub(crate) struct Callback(Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl Callback {
+    pub(crate) fn new(f: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Wraps the callback so it only runs once no newer call has arrived within `delay`
+    ///
+    /// Calls that are overtaken return nothing, as `fzf` has already cancelled the command that
+    /// made them
+    pub(crate) fn debounced(self, delay: Duration) -> Self {
+        let latest = Arc::new(AtomicUsize::new(0));
+        Self::new(move |argument| {
+            let call = latest.fetch_add(1, Ordering::SeqCst) + 1;
+            thread::sleep(delay);
+            if latest.load(Ordering::SeqCst) != call {
+                return String::new();
+            }
+            self.call(argument)
+        })
+    }
+
+    /// Runs the closure with the argument a command passed
+    pub(crate) fn call(&self, argument: &str) -> String {
+        (self.0)(argument)
+    }
+}
+
+impl fmt::Debug for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// Handles the current process being run by `fzf` to call a rust closure, such as the one given
+/// to the `preview_fn` option
+///
+/// This must be called at the very start of `main`, before anything is printed, as the output of
+/// the process is what `fzf` shows. If the process wasn't run by `fzf`, this returns straight
+/// away, otherwise the process exits once the callback has been answered
+pub fn handle_self_invocation() {
+    let mut args = env::args_os().skip(1);
+    if args.next().as_deref() != Some(OsStr::new(CALLBACK_ARG)) {
+        return;
+    }
+
+    let name = args.next().unwrap_or_default();
+    let argument = args.next().unwrap_or_default();
+    let result = call(&name.to_string_lossy(), &argument.to_string_lossy());
+    match result {
+        Ok(()) => process::exit(0),
+        Err(e) => {
+            eprintln!("fzf-wrapped: {}", e);
+            process::exit(1)
+        }
+    }
+}
+
+/// Sends a request to the callback server of the session that ran this process, printing the
+/// response
+fn call(name: &str, argument: &str) -> io::Result<()> {
+    let missing = |var| io::Error::new(io::ErrorKind::NotFound, format!("{} is not set", var));
+    let addr = env::var(ADDR_ENV).map_err(|_| missing(ADDR_ENV))?;
+    let token = env::var(TOKEN_ENV).map_err(|_| missing(TOKEN_ENV))?;
+
+    let response = request(&addr, &token, name, argument)?;
+    io::stdout().write_all(&response)
+}
+
+/// Sends a request to the callback server at the given address, returning its response
+fn request(addr: &str, token: &str, name: &str, argument: &str) -> io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(addr)?;
+    write!(stream, "{}\n{}\n{}", token, name, argument)?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut response = vec![];
+    stream.read_to_end(&mut response)?;
+    Ok(response)
+}
+
+#[derive(Debug)]
+/// A server on localhost answering callbacks from the commands run by one `fzf` session
+pub(crate) struct CallbackServer {
+    addr: SocketAddr,
+    token: String,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CallbackServer {
+    /// Starts the server, answering requests for each of the named callbacks on their own thread
+    pub(crate) fn spawn(callbacks: Vec<(&'static str, Callback)>) -> Result<Self, FzfError> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).map_err(FzfError::Callback)?;
+        let addr = listener.local_addr().map_err(FzfError::Callback)?;
+        let token = new_token();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_token = token.clone();
+        let thread_stop = Arc::clone(&stop);
+        let thread = thread::spawn(move || loop {
+            let accepted = listener.accept();
+            // Stopping the server connects to it, to wake this thread up
+            if thread_stop.load(Ordering::Relaxed) {
+                break;
+            }
+            let stream = match accepted {
+                Ok((stream, _)) => stream,
+                // Accepting can fail when the process has run out of file descriptors, so wait a
+                // while before trying again
+                Err(_) => {
+                    thread::sleep(ACCEPT_RETRY_DELAY);
+                    continue;
+                }
+            };
+            let token = thread_token.clone();
+            let callbacks = callbacks.clone();
+            // Not joined, so a slow callback can't hold up the session once `fzf` has exited
+            thread::spawn(move || {
+                // A failed callback just leaves `fzf` without a response to show
+                let _ = answer(stream, &token, &callbacks);
+            });
+        });
+
+        Ok(Self {
+            addr,
+            token,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Gets the environment variables commands need to reach the server
+    pub(crate) fn env(&self) -> [(&'static str, String); 2] {
+        [
+            (ADDR_ENV, self.addr.to_string()),
+            (TOKEN_ENV, self.token.clone()),
+        ]
+    }
+
+    /// Creates the command `fzf` runs to call the named callback, with the argument `fzf`
+    /// replaces the placeholder with
+    pub(crate) fn command(name: &str, placeholder: &str) -> Result<String, FzfError> {
+        let executable = env::current_exe().map_err(FzfError::Callback)?;
+        Ok(format!(
+            "{} {} {} {}",
+            shell_quote(&executable.to_string_lossy()),
+            CALLBACK_ARG,
+            name,
+            placeholder
+        ))
+    }
+}
+
+impl Drop for CallbackServer {
+    /// Stops the server, leaving any callbacks still running to finish on their own
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // If the server can't be woken up, it's left to stop once something else connects
+        if TcpStream::connect(self.addr).is_ok() {
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+/// Reads a request from the stream, and writes the response of the callback it names
+fn answer(mut stream: TcpStream, token: &str, callbacks: &[(&str, Callback)]) -> io::Result<()> {
+    // A connection that never finishes its request would otherwise stop the server from stopping
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut request = String::new();
+    stream.read_to_string(&mut request)?;
+
+    let mut parts = request.splitn(3, '\n');
+    if parts.next() != Some(token) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "wrong token",
+        ));
+    }
+    let name = parts.next().unwrap_or_default();
+    let argument = parts.next().unwrap_or_default();
+
+    if let Some((_, callback)) = callbacks.iter().find(|(x, _)| *x == name) {
+        stream.write_all(callback.call(argument).as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Creates a token that's hard to guess, so other local processes can't use the server
+pub(crate) fn new_token() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|x| x.as_nanos())
+        .unwrap_or_default();
+    (0..2)
+        .map(|i| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(nanos);
+            hasher.write_u32(process::id());
+            hasher.write_usize(i);
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callbacks_are_answered_with_the_right_token() {
+        let upper = Callback::new(|x| x.to_uppercase());
+        let server = CallbackServer::spawn(vec![("preview", upper)]).unwrap();
+        let addr = server.addr.to_string();
+
+        let response = request(&addr, &server.token, "preview", "multi\nline").unwrap();
+        assert_eq!(response, b"MULTI\nLINE");
+
+        assert!(request(&addr, "wrong", "preview", "red")
+            .unwrap()
+            .is_empty());
+        assert!(request(&addr, &server.token, "reload", "red")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn idle_connections_dont_stop_the_server_stopping() {
+        let echo = Callback::new(|x| x.to_string());
+        let server = CallbackServer::spawn(vec![("preview", echo)]).unwrap();
+        let _idle = TcpStream::connect(server.addr).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        let (stopped, done) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            drop(server);
+            stopped.send(()).unwrap();
+        });
+        assert!(done.recv_timeout(REQUEST_TIMEOUT * 5).is_ok());
+    }
+
+    #[test]
+    fn running_callbacks_dont_stop_the_server_stopping() {
+        let (started, running) = std::sync::mpsc::channel();
+        let slow = Callback::new(move |x| {
+            let _ = started.send(());
+            thread::sleep(REQUEST_TIMEOUT * 5);
+            x.to_string()
+        });
+        let server = CallbackServer::spawn(vec![("preview", slow)]).unwrap();
+        let (addr, token) = (server.addr.to_string(), server.token.clone());
+        thread::spawn(move || request(&addr, &token, "preview", "red"));
+        running.recv_timeout(REQUEST_TIMEOUT).unwrap();
+
+        let (stopped, done) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            drop(server);
+            stopped.send(()).unwrap();
+        });
+        assert!(done.recv_timeout(REQUEST_TIMEOUT).is_ok());
+    }
+
+    #[test]
+    fn overtaken_calls_are_dropped() {
+        let Callback(echo) = Callback::new(|x| x.to_string()).debounced(Duration::from_millis(50));
+
+        let stale = {
+            let echo = Arc::clone(&echo);
+            thread::spawn(move || echo("r"))
+        };
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(echo("re"), "re");
+        assert_eq!(stale.join().unwrap(), "");
+    }
+}
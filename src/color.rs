@@ -0,0 +1,546 @@
+//! This module contains the [`ColorScheme`] struct, which sets the colors of each part of `fzf`'s
+//! ui on top of one of its base themes
+
+use std::{fmt, str::FromStr};
+
+use derive_builder::Builder;
+
+use crate::error::FzfError;
+use crate::options::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent the parts of `fzf`'s ui that can be colored
+pub enum ColorElement {
+    /// Text
+    Fg,
+    /// Background
+    Bg,
+    /// Highlighted substrings
+    Hl,
+    /// Text of the current line
+    CurrentFg,
+    /// Background of the current line
+    CurrentBg,
+    /// Highlighted substrings of the current line
+    CurrentHl,
+    /// Text of selected lines
+    SelectedFg,
+    /// Background of selected lines
+    SelectedBg,
+    /// Highlighted substrings of selected lines
+    SelectedHl,
+    /// Text of the preview window
+    PreviewFg,
+    /// Background of the preview window
+    PreviewBg,
+    /// The gutter on the left of the list
+    Gutter,
+    /// The query
+    Query,
+    /// The query when search is disabled
+    Disabled,
+    /// Info line, such as the match counter
+    Info,
+    /// The prompt
+    Prompt,
+    /// The pointer to the current line
+    Pointer,
+    /// The marker on selected lines
+    Marker,
+    /// The spinner shown while items are being read
+    Spinner,
+    /// The header
+    Header,
+    /// The border
+    Border,
+    /// The border label
+    Label,
+    /// The horizontal separator on the info line
+    Separator,
+    /// The scrollbar
+    Scrollbar,
+    /// The border of the preview window
+    PreviewBorder,
+    /// The border label of the preview window
+    PreviewLabel,
+    /// The scrollbar of the preview window
+    PreviewScrollbar,
+    /// The border of the input section
+    InputBorder,
+    /// The border label of the input section
+    InputLabel,
+    /// The border of the list section
+    ListBorder,
+    /// The border label of the list section
+    ListLabel,
+    /// The border of the header section
+    HeaderBorder,
+    /// The border label of the header section
+    HeaderLabel,
+}
+
+impl ColorElement {
+    /// Every part of the ui that can be colored
+    pub(crate) const ALL: [Self; 33] = [
+        Self::Fg,
+        Self::Bg,
+        Self::Hl,
+        Self::CurrentFg,
+        Self::CurrentBg,
+        Self::CurrentHl,
+        Self::SelectedFg,
+        Self::SelectedBg,
+        Self::SelectedHl,
+        Self::PreviewFg,
+        Self::PreviewBg,
+        Self::Gutter,
+        Self::Query,
+        Self::Disabled,
+        Self::Info,
+        Self::Prompt,
+        Self::Pointer,
+        Self::Marker,
+        Self::Spinner,
+        Self::Header,
+        Self::Border,
+        Self::Label,
+        Self::Separator,
+        Self::Scrollbar,
+        Self::PreviewBorder,
+        Self::PreviewLabel,
+        Self::PreviewScrollbar,
+        Self::InputBorder,
+        Self::InputLabel,
+        Self::ListBorder,
+        Self::ListLabel,
+        Self::HeaderBorder,
+        Self::HeaderLabel,
+    ];
+}
+
+impl fmt::Display for ColorElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Fg => "fg",
+            Self::Bg => "bg",
+            Self::Hl => "hl",
+            Self::CurrentFg => "fg+",
+            Self::CurrentBg => "bg+",
+            Self::CurrentHl => "hl+",
+            Self::SelectedFg => "selected-fg",
+            Self::SelectedBg => "selected-bg",
+            Self::SelectedHl => "selected-hl",
+            Self::PreviewFg => "preview-fg",
+            Self::PreviewBg => "preview-bg",
+            Self::Gutter => "gutter",
+            Self::Query => "query",
+            Self::Disabled => "disabled",
+            Self::Info => "info",
+            Self::Prompt => "prompt",
+            Self::Pointer => "pointer",
+            Self::Marker => "marker",
+            Self::Spinner => "spinner",
+            Self::Header => "header",
+            Self::Border => "border",
+            Self::Label => "label",
+            Self::Separator => "separator",
+            Self::Scrollbar => "scrollbar",
+            Self::PreviewBorder => "preview-border",
+            Self::PreviewLabel => "preview-label",
+            Self::PreviewScrollbar => "preview-scrollbar",
+            Self::InputBorder => "input-border",
+            Self::InputLabel => "input-label",
+            Self::ListBorder => "list-border",
+            Self::ListLabel => "list-label",
+            Self::HeaderBorder => "header-border",
+            Self::HeaderLabel => "header-label",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl FromStr for ColorElement {
+    type Err = FzfError;
+
+    /// Parses the name `fzf` uses for the element, such as `fg+` or `current-fg`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = match s.to_lowercase().as_str() {
+            "current-fg" => "fg+".to_string(),
+            "current-bg" => "bg+".to_string(),
+            "current-hl" => "hl+".to_string(),
+            name => name.to_string(),
+        };
+        find_by_name(&Self::ALL, &name)
+            .ok_or_else(|| FzfError::InvalidTheme(format!("unknown color element {}", s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent the 16 ANSI colors, which follow the terminal's palette
+pub enum AnsiColor {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl AnsiColor {
+    /// Every ANSI color, in the order of their numbers
+    const ALL: [Self; 16] = [
+        Self::Black,
+        Self::Red,
+        Self::Green,
+        Self::Yellow,
+        Self::Blue,
+        Self::Magenta,
+        Self::Cyan,
+        Self::White,
+        Self::BrightBlack,
+        Self::BrightRed,
+        Self::BrightGreen,
+        Self::BrightYellow,
+        Self::BrightBlue,
+        Self::BrightMagenta,
+        Self::BrightCyan,
+        Self::BrightWhite,
+    ];
+}
+
+impl fmt::Display for AnsiColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Black => "black",
+            Self::Red => "red",
+            Self::Green => "green",
+            Self::Yellow => "yellow",
+            Self::Blue => "blue",
+            Self::Magenta => "magenta",
+            Self::Cyan => "cyan",
+            Self::White => "white",
+            Self::BrightBlack => "bright-black",
+            Self::BrightRed => "bright-red",
+            Self::BrightGreen => "bright-green",
+            Self::BrightYellow => "bright-yellow",
+            Self::BrightBlue => "bright-blue",
+            Self::BrightMagenta => "bright-magenta",
+            Self::BrightCyan => "bright-cyan",
+            Self::BrightWhite => "bright-white",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent a color `fzf` can draw with
+pub enum ColorValue {
+    /// The terminal's default color
+    #[default]
+    Default,
+    /// One of the 16 ANSI colors
+    Ansi(AnsiColor),
+    /// One of the 256 colors, where `0` to `15` are the ANSI colors
+    Indexed(u8),
+    /// A 24-bit color, written as `#rrggbb`
+    Rgb(u8, u8, u8),
+}
+
+impl fmt::Display for ColorValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "-1"),
+            Self::Ansi(color) => write!(f, "{}", color),
+            Self::Indexed(index) => write!(f, "{}", index),
+            Self::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+impl FromStr for ColorValue {
+    type Err = FzfError;
+
+    /// Parses a color written the way `fzf` takes it, such as `-1`, `red`, `208` or `#282c34`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FzfError::InvalidTheme(format!("invalid color {}", s));
+        let s = s.to_lowercase();
+
+        if s == "-1" || s == "default" {
+            return Ok(Self::Default);
+        }
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|x| u8::from_str_radix(x, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Self::Rgb(r, g, b)),
+                _ => Err(invalid()),
+            };
+        }
+        if let Ok(index) = s.parse::<u8>() {
+            return Ok(Self::Indexed(index));
+        }
+        find_by_name(&AnsiColor::ALL, &s)
+            .map(Self::Ansi)
+            .ok_or_else(invalid)
+    }
+}
+
+impl From<AnsiColor> for ColorValue {
+    fn from(value: AnsiColor) -> Self {
+        Self::Ansi(value)
+    }
+}
+
+impl From<u8> for ColorValue {
+    fn from(value: u8) -> Self {
+        Self::Indexed(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent the text attributes `fzf` can draw with
+pub enum ColorAttr {
+    /// Clears the attributes of the base theme
+    Regular,
+    Bold,
+    Underline,
+    Reverse,
+    Dim,
+    Italic,
+    Strikethrough,
+}
+
+impl ColorAttr {
+    /// Every text attribute
+    const ALL: [Self; 7] = [
+        Self::Regular,
+        Self::Bold,
+        Self::Underline,
+        Self::Reverse,
+        Self::Dim,
+        Self::Italic,
+        Self::Strikethrough,
+    ];
+}
+
+impl fmt::Display for ColorAttr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Regular => "regular",
+            Self::Bold => "bold",
+            Self::Underline => "underline",
+            Self::Reverse => "reverse",
+            Self::Dim => "dim",
+            Self::Italic => "italic",
+            Self::Strikethrough => "strikethrough",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Struct that represents how one part of the ui is drawn, with a color and any attributes
+///
+/// Can be created from a [`ColorValue`], [`AnsiColor`] or 256-color index, or from a
+/// [`ColorAttr`] to only change the attributes
+pub struct ColorSpec {
+    color: Option<ColorValue>,
+    attrs: Vec<ColorAttr>,
+}
+
+impl ColorSpec {
+    /// Creates a spec that draws with the given color
+    pub fn new(color: impl Into<ColorValue>) -> Self {
+        Self {
+            color: Some(color.into()),
+            attrs: vec![],
+        }
+    }
+
+    /// Adds an attribute to the spec, such as [`ColorAttr::Bold`]
+    pub fn attr(mut self, attr: ColorAttr) -> Self {
+        if !self.attrs.contains(&attr) {
+            self.attrs.push(attr);
+        }
+        self
+    }
+}
+
+impl fmt::Display for ColorSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .color
+            .iter()
+            .map(ToString::to_string)
+            .chain(self.attrs.iter().map(ToString::to_string))
+            .collect();
+        write!(f, "{}", parts.join(":"))
+    }
+}
+
+impl FromStr for ColorSpec {
+    type Err = FzfError;
+
+    /// Parses a color and attributes separated by `:`, such as `#282c34:bold` or `underline`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut spec = Self {
+            color: None,
+            attrs: vec![],
+        };
+        for part in s.split(':') {
+            match find_by_name(&ColorAttr::ALL, &part.to_lowercase()) {
+                Some(attr) => spec = spec.attr(attr),
+                None if spec.color.is_none() => spec.color = Some(part.parse()?),
+                None => return Err(FzfError::InvalidTheme(format!("invalid color spec {}", s))),
+            }
+        }
+        Ok(spec)
+    }
+}
+
+impl From<ColorValue> for ColorSpec {
+    fn from(value: ColorValue) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<AnsiColor> for ColorSpec {
+    fn from(value: AnsiColor) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<u8> for ColorSpec {
+    fn from(value: u8) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<ColorAttr> for ColorSpec {
+    fn from(value: ColorAttr) -> Self {
+        Self {
+            color: None,
+            attrs: vec![value],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Builder, Default, PartialEq, Eq, Hash)]
+#[builder(build_fn(error = "FzfError"))]
+/// Struct that represents the colors of `fzf`, passed with `--color`
+///
+/// Each part of the ui that isn't set keeps the color of the base theme. A [`Color`] can be used
+/// anywhere a scheme is expected, to use the base theme on its own
+pub struct ColorScheme {
+    /// The theme to start from
+    #[builder(setter(into), default = "Color::Dark")]
+    base: Color,
+    /// The parts of the ui drawn differently to the base theme
+    #[builder(setter(custom), default = "vec![]")]
+    elements: Vec<(ColorElement, ColorSpec)>,
+}
+
+impl ColorSchemeBuilder {
+    /// Sets how one part of the ui is drawn, replacing anything it was set to before
+    ///
+    /// # Parameters
+    ///
+    /// - `element` The part of the ui to draw differently
+    /// - `spec` The color and attributes to draw it with, such as `AnsiColor::Red`,
+    ///   `ColorValue::Rgb(0x28, 0x2c, 0x34)` or `ColorSpec::new(4).attr(ColorAttr::Bold)`
+    pub fn element(&mut self, element: ColorElement, spec: impl Into<ColorSpec>) -> &mut Self {
+        let elements = self.elements.get_or_insert_with(Vec::new);
+        elements.retain(|(x, _)| *x != element);
+        elements.push((element, spec.into()));
+        self
+    }
+}
+
+impl ColorScheme {
+    /// Creates a [`ColorSchemeBuilder`]
+    pub fn builder() -> ColorSchemeBuilder {
+        ColorSchemeBuilder::default()
+    }
+}
+
+impl From<Color> for ColorScheme {
+    fn from(value: Color) -> Self {
+        Self {
+            base: value,
+            elements: vec![],
+        }
+    }
+}
+
+impl fmt::Display for ColorScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.base)?;
+        for (element, spec) in &self.elements {
+            write!(f, ",{}:{}", element, spec)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the value that `fzf` calls by the given name
+fn find_by_name<T: Copy + fmt::Display>(values: &[T], name: &str) -> Option<T> {
+    values.iter().copied().find(|x| x.to_string() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheme_is_rendered_as_a_color_spec() {
+        let scheme = ColorScheme::builder()
+            .base(Color::Light)
+            .element(ColorElement::Fg, 7)
+            .element(ColorElement::Hl, AnsiColor::BrightRed)
+            .element(ColorElement::CurrentBg, ColorValue::Rgb(0x28, 0x2c, 0x34))
+            .element(
+                ColorElement::Prompt,
+                ColorSpec::new(4).attr(ColorAttr::Bold),
+            )
+            .element(ColorElement::Header, ColorAttr::Italic)
+            .element(ColorElement::Bg, ColorValue::Default)
+            .element(ColorElement::Fg, 15)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            scheme.to_string(),
+            "light,hl:bright-red,bg+:#282c34,prompt:4:bold,header:italic,bg:-1,fg:15"
+        );
+        assert_eq!(ColorScheme::from(Color::Bw).to_string(), "bw");
+    }
+
+    #[test]
+    fn specs_are_parsed() {
+        let parse = |x: &str| x.parse::<ColorSpec>().ok().map(|x| x.to_string());
+
+        assert_eq!(parse("#282C34:bold"), Some("#282c34:bold".to_string()));
+        assert_eq!(parse("bright-red"), Some("bright-red".to_string()));
+        assert_eq!(parse("underline:208"), Some("208:underline".to_string()));
+        assert_eq!(parse("default"), Some("-1".to_string()));
+        assert_eq!(parse("#28"), None);
+        assert_eq!(parse("red:blue"), None);
+        assert_eq!(parse("256"), None);
+        assert_eq!(
+            "current-bg".parse::<ColorElement>().ok(),
+            Some(ColorElement::CurrentBg)
+        );
+    }
+}
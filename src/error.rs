@@ -0,0 +1,132 @@
+//! This module contains the [`FzfError`] enum, which represents everything that can go wrong while
+//! running `fzf`
+
+use std::{error::Error, fmt, io, string::FromUtf8Error};
+
+use derive_builder::UninitializedFieldError;
+
+use crate::version::FzfVersion;
+
+#[derive(Debug)]
+/// Enum to represent the errors that can occur while running `fzf`
+pub enum FzfError {
+    /// `fzf`'s stdin has been closed, so no more items can be added
+    StdinClosed,
+    /// The `fzf` executable could not be found
+    NotInstalled,
+    /// `fzf` could not be spawned
+    Spawn(io::Error),
+    /// An item could not be written to `fzf`
+    Write(io::Error),
+    /// `fzf` stopped reading items, usually because the user has already made their selection
+    BrokenPipe,
+    /// Waiting for `fzf` to exit failed
+    Wait(io::Error),
+    /// `fzf` exited with an exit code it does not document, or failed while filtering items
+    ExitCode {
+        /// The exit code of `fzf`, or `None` if it was terminated by a signal
+        code: Option<i32>,
+        /// Everything `fzf` wrote to stderr
+        stderr: String,
+    },
+    /// The output of `fzf` was not valid UTF-8
+    InvalidUtf8(FromUtf8Error),
+    /// The configuration given to the [`crate::FzfBuilder`] was invalid
+    Builder(String),
+    /// The server that lets `fzf` call rust closures could not be started
+    Callback(io::Error),
+    /// A request to the http server `fzf` starts with the `listen` option failed
+    Listen(io::Error),
+    /// A key binding could not be turned into a `--bind` argument
+    InvalidBind(String),
+    /// A key has no name `fzf` understands, or an event was used where only keys can be
+    InvalidKey(String),
+    /// A color or theme could not be parsed, or a theme file could not be read
+    InvalidTheme(String),
+    /// The output of `fzf --version` could not be parsed
+    UnknownVersion(String),
+    /// An option isn't supported by the installed version of `fzf`, when using
+    /// [`crate::VersionPolicy::Error`]
+    UnsupportedOption {
+        /// The option that isn't supported
+        option: String,
+        /// The version of `fzf` that added the option
+        required: FzfVersion,
+        /// The version of `fzf` that is installed
+        found: FzfVersion,
+    },
+}
+
+impl FzfError {
+    /// Creates the error for a failed spawn, detecting when `fzf` is not installed
+    pub(crate) fn spawn(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::NotInstalled,
+            _ => Self::Spawn(error),
+        }
+    }
+
+    /// Creates the error for a failed write, detecting when `fzf` has closed its stdin
+    pub(crate) fn write(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::BrokenPipe => Self::BrokenPipe,
+            _ => Self::Write(error),
+        }
+    }
+}
+
+impl fmt::Display for FzfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StdinClosed => write!(f, "fzf's stdin has been closed"),
+            Self::NotInstalled => write!(f, "fzf is not installed"),
+            Self::Spawn(e) => write!(f, "failed to spawn fzf: {}", e),
+            Self::Write(e) => write!(f, "failed to write to fzf: {}", e),
+            Self::BrokenPipe => write!(f, "fzf is no longer reading items"),
+            Self::Wait(e) => write!(f, "failed to wait for fzf: {}", e),
+            Self::ExitCode {
+                code: Some(code),
+                stderr,
+            } => {
+                write!(f, "fzf exited with code {}: {}", code, stderr)
+            }
+            Self::ExitCode { code: None, stderr } => {
+                write!(f, "fzf was terminated by a signal: {}", stderr)
+            }
+            Self::InvalidUtf8(e) => write!(f, "fzf output was not valid UTF-8: {}", e),
+            Self::Builder(e) => write!(f, "failed to build fzf: {}", e),
+            Self::Callback(e) => write!(f, "failed to start the callback server: {}", e),
+            Self::Listen(e) => write!(f, "request to fzf's listen server failed: {}", e),
+            Self::InvalidBind(e) => write!(f, "invalid key binding: {}", e),
+            Self::InvalidKey(e) => write!(f, "invalid key: {}", e),
+            Self::InvalidTheme(e) => write!(f, "invalid theme: {}", e),
+            Self::UnknownVersion(output) => write!(f, "unknown fzf version: {}", output),
+            Self::UnsupportedOption {
+                option,
+                required,
+                found,
+            } => write!(
+                f,
+                "{} requires fzf {}, but {} is installed",
+                option, required, found
+            ),
+        }
+    }
+}
+
+impl Error for FzfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Spawn(e) | Self::Write(e) | Self::Wait(e) => Some(e),
+            Self::Callback(e) | Self::Listen(e) => Some(e),
+            Self::InvalidUtf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<UninitializedFieldError> for FzfError {
+    fn from(value: UninitializedFieldError) -> Self {
+        Self::Builder(value.to_string())
+    }
+}
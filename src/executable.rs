@@ -0,0 +1,133 @@
+//! This module contains the functions used to find the `fzf` executable
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// The environment variable that overrides the `fzf` executable, when the `executable` option
+/// isn't set
+pub const FZF_BIN_ENV: &str = "FZF_WRAPPED_BIN";
+
+/// The name of the `fzf` executable
+#[cfg(windows)]
+const FZF_NAME: &str = "fzf.exe";
+/// The name of the `fzf` executable
+#[cfg(not(windows))]
+const FZF_NAME: &str = "fzf";
+
+/// Searches the directories in `PATH` for the `fzf` executable
+///
+/// This can be used to check that `fzf` is installed before running it
+///
+/// # Returns
+///
+/// The path to `fzf`, or `None` if it isn't in any of the directories in `PATH`
+pub fn find_fzf() -> Option<PathBuf> {
+    find_in(FZF_NAME, env::split_paths(&env::var_os("PATH")?))
+}
+
+/// Gets the executable to run `fzf` with, from the `executable` option, the [`FZF_BIN_ENV`]
+/// environment variable, or `PATH`, in that order
+pub(crate) fn resolve(executable: Option<&Path>) -> Option<PathBuf> {
+    match executable {
+        Some(executable) => Some(executable.to_path_buf()),
+        None => env::var_os(FZF_BIN_ENV)
+            .filter(|x| !x.is_empty())
+            .map(PathBuf::from)
+            .or_else(find_fzf),
+    }
+}
+
+/// Searches the given directories for an executable file with the given name
+fn find_in(name: &str, dirs: impl IntoIterator<Item = PathBuf>) -> Option<PathBuf> {
+    dirs.into_iter()
+        .map(|dir| dir.join(name))
+        .find(|path| is_executable(path))
+}
+
+/// Checks whether the given path is a file that can be executed
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|x| x.is_file() && x.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Checks whether the given path is a file that can be executed
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// A shell script standing in for `fzf` in tests, which is deleted when dropped
+#[cfg(all(test, unix))]
+pub(crate) struct StandIn {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+#[cfg(all(test, unix))]
+impl StandIn {
+    /// Gets the path of the script, which must not be used once the stand-in is dropped
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(all(test, unix))]
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Creates a shell script that stands in for `fzf` in tests, ignoring the arguments it's given
+///
+/// Each script gets its own directory, as running a file while another test rewrites it fails
+#[cfg(all(test, unix))]
+pub(crate) fn stand_in(name: &str, script: &str) -> StandIn {
+    use std::{
+        fs,
+        os::unix::fs::PermissionsExt,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = env::temp_dir().join(format!(
+        "fzf-wrapped-stand-in-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    StandIn { dir, path }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    #[test]
+    fn only_executable_files_are_found() {
+        let dir = env::temp_dir().join(format!("fzf-wrapped-find-{}", std::process::id()));
+        let empty = dir.join("empty");
+        let bin = dir.join("bin");
+        fs::create_dir_all(&empty).unwrap();
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(empty.join("fzf"), "").unwrap();
+        fs::write(bin.join("fzf"), "").unwrap();
+        fs::set_permissions(bin.join("fzf"), fs::Permissions::from_mode(0o755)).unwrap();
+
+        let found = find_in("fzf", [empty.clone(), bin.clone()]);
+        assert_eq!(found, Some(bin.join("fzf")));
+        assert_eq!(find_in("fzf", [empty]), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+//! This module contains the background thread that feeds items to `fzf`, so that slow or endless
+//! producers don't stop `fzf` from returning a selection
+
+use std::{
+    io::Write,
+    process::ChildStdin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError, SyncSender},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::error::FzfError;
+use crate::session::ItemTracker;
+
+/// How often the feeder thread checks whether `fzf` has exited while waiting for items
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How many items can be queued for the feeder thread before senders block, so that a fast or
+/// endless producer can't outrun `fzf` and use up memory
+const QUEUE_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone)]
+/// A handle for sending items to `fzf` from any thread, created by [`crate::FzfSession::sender`]
+///
+/// Items are written to `fzf` by a background thread in the order they are sent. `fzf` is told
+/// that there are no more items once every sender has been dropped
+pub struct ItemSender {
+    sender: SyncSender<String>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ItemSender {
+    /// Sends an item to be added to the `fzf` selection ui
+    ///
+    /// Blocks while the queue of items waiting to be written to `fzf` is full
+    ///
+    /// # Parameters
+    ///
+    /// - `item` The item to add
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::BrokenPipe`] if `fzf` is no longer reading items, which producers
+    /// should treat as a signal to stop
+    pub fn send(&self, item: impl Into<String>) -> Result<(), FzfError> {
+        if self.is_stopped() {
+            return Err(FzfError::BrokenPipe);
+        }
+        self.sender
+            .send(item.into())
+            .map_err(|_| FzfError::BrokenPipe)
+    }
+
+    /// Returns `true` once `fzf` is no longer reading items
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug)]
+/// The background thread writing items to `fzf`'s stdin
+pub(crate) struct Feeder {
+    /// The sender owned by the session, which is dropped once the session stops adding items
+    sender: Option<ItemSender>,
+    /// Set once `fzf` has exited, telling the feeder and any producers to stop
+    stop: Arc<AtomicBool>,
+}
+
+impl Feeder {
+    /// Moves `stdin` onto a background thread, which writes every item it is sent to `fzf`
+    pub(crate) fn spawn(mut stdin: ChildStdin, tracker: Arc<Mutex<ItemTracker>>) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<String>(QUEUE_CAPACITY);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_stop = Arc::clone(&stop);
+        thread::spawn(move || loop {
+            match receiver.recv_timeout(STOP_POLL_INTERVAL) {
+                Ok(_) if thread_stop.load(Ordering::Relaxed) => break,
+                Ok(item) => {
+                    let line = tracker
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .line(item);
+                    // A closed pipe means the user is done, so there's no point feeding any more
+                    if stdin.write_all(line.as_bytes()).is_err() {
+                        thread_stop.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                    tracker
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .sent();
+                }
+                Err(RecvTimeoutError::Timeout) if !thread_stop.load(Ordering::Relaxed) => (),
+                // Either every sender has been dropped, or `fzf` has exited
+                Err(_) => break,
+            }
+        });
+
+        Self {
+            sender: Some(ItemSender {
+                sender,
+                stop: Arc::clone(&stop),
+            }),
+            stop,
+        }
+    }
+
+    /// Gets a new handle for sending items to the feeder
+    ///
+    /// Returns `None` if the session has already stopped adding items
+    pub(crate) fn sender(&self) -> Option<ItemSender> {
+        self.sender.clone()
+    }
+
+    /// Spawns a thread that sends every item from the given iterator to the feeder, stopping early
+    /// once `fzf` is no longer reading items
+    pub(crate) fn feed<I>(&self, items: I) -> Result<(), FzfError>
+    where
+        I: IntoIterator + Send + 'static,
+        I::Item: Into<String>,
+    {
+        let sender = self.sender().ok_or(FzfError::StdinClosed)?;
+        thread::spawn(move || {
+            let mut items = items.into_iter();
+            // Checked before asking for each item, as the iterator may block until it has one
+            while !sender.is_stopped() {
+                let Some(item) = items.next() else { break };
+                if sender.send(item).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Drops the session's sender, so that `fzf`'s stdin is closed once every other sender has
+    /// been dropped
+    pub(crate) fn close(&mut self) {
+        drop(self.sender.take());
+    }
+
+    /// Tells the feeder and any producers to stop, as `fzf` has exited
+    pub(crate) fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::executable::{self, StandIn};
+    use crate::outcome::FzfOutcome;
+    use crate::Fzf;
+
+    use super::*;
+
+    fn run(stand_in: &StandIn) -> crate::FzfSession {
+        Fzf::builder()
+            .executable(stand_in.path())
+            .build()
+            .unwrap()
+            .run()
+            .unwrap()
+    }
+
+    #[test]
+    fn stdin_is_closed_once_every_sender_is_dropped() {
+        let stand_in = executable::stand_in("count", "wc -l");
+        let mut session = run(&stand_in);
+        let sender = session.sender().unwrap();
+
+        let producer = thread::spawn(move || {
+            for colour in ["red", "green", "blue"] {
+                sender.send(colour).unwrap();
+            }
+        });
+        let outcome = session.output().unwrap();
+        producer.join().unwrap();
+        assert_eq!(outcome, FzfOutcome::Selected("3".to_string()));
+    }
+
+    #[test]
+    fn endless_producers_are_stopped_once_fzf_exits() {
+        let stand_in = executable::stand_in("first-line", "IFS= read -r x; echo \"$x\"");
+        let mut session = run(&stand_in);
+        let sender = session.sender().unwrap();
+        session.feed((0..).map(|x: u64| x.to_string())).unwrap();
+
+        assert_eq!(
+            session.output().unwrap(),
+            FzfOutcome::Selected("0".to_string())
+        );
+        assert!(sender.is_stopped());
+        assert!(matches!(sender.send("red"), Err(FzfError::BrokenPipe)));
+    }
+
+    #[test]
+    fn endless_producers_only_run_ahead_of_fzf_by_the_queue() {
+        let stand_in = executable::stand_in("no-read", "sleep 0.2; echo red");
+        let mut session = run(&stand_in);
+        let pulled = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&pulled);
+        session
+            .feed((0..).map(move |x: u64| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                x.to_string()
+            }))
+            .unwrap();
+
+        assert_eq!(
+            session.output().unwrap(),
+            FzfOutcome::Selected("red".to_string())
+        );
+        thread::sleep(STOP_POLL_INTERVAL);
+        let stopped_at = pulled.load(Ordering::Relaxed);
+        thread::sleep(STOP_POLL_INTERVAL);
+        assert_eq!(pulled.load(Ordering::Relaxed), stopped_at);
+        // Only the queue and the pipe's buffer should have been filled while `fzf` wasn't reading
+        assert!(stopped_at < 100_000);
+    }
+}
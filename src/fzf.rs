@@ -1,30 +1,96 @@
 use std::{
-    io::{self, Write},
-    process::{Child, ChildStdin, Command, Stdio},
+    env,
+    ffi::OsString,
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
 };
 
 use derive_builder::Builder;
 
+use crate::action::{self, Action, Binding};
+#[cfg(feature = "tokio")]
+use crate::async_session::AsyncFzfSession;
+use crate::callback::{Callback, CallbackServer};
+use crate::color::ColorScheme;
+use crate::error::FzfError;
+use crate::executable;
+use crate::item::{self, FzfItem};
+use crate::key::{self, Key};
+#[cfg(feature = "listen")]
+use crate::listen::{self, FzfClient, Listen};
 use crate::options::*;
+use crate::preview::PreviewWindow;
+use crate::session::{FzfSession, OutputFormat, StartMarker};
+use crate::version::{self, FzfVersion, VersionPolicy};
 
+/// The environment variables `fzf` reads the user's defaults from
+const DEFAULT_OPTS_ENV: [&str; 3] = [
+    "FZF_DEFAULT_OPTS",
+    "FZF_DEFAULT_OPTS_FILE",
+    "FZF_DEFAULT_COMMAND",
+];
+/// The options that reset the user's defaults that would change what `fzf` prints
+const DEFAULT_OPTS_RESETS: [&str; 9] = [
+    "--no-multi",
+    "--no-print-query",
+    "--no-expect",
+    "--no-select-1",
+    "--no-exit-0",
+    "--no-read0",
+    "--no-print0",
+    "--no-preview",
+    "--no-tac",
+];
+/// The options without a `--no-` form that change how items are split into fields, along with
+/// their short form and the argument that undoes them
+///
+/// These are only reset when the user's defaults set them, as `--accept-nth` needs a newer version
+/// of `fzf`. The default delimiter can't be set again, so runs of spaces and tabs are used, which
+/// only differs for items that start with whitespace
+const FIELD_OPTS_RESETS: [(&str, Option<&str>, &str); 4] = [
+    ("--delimiter", Some("-d"), "--delimiter=[ \t]+"),
+    ("--nth", Some("-n"), "--nth=.."),
+    ("--with-nth", None, "--with-nth=.."),
+    ("--accept-nth", None, "--accept-nth=.."),
+];
+const DEFAULT_LIVE_SEARCH_DEBOUNCE: Duration = Duration::from_millis(100);
 const DEFAULT_PROMPT: &str = "> ";
 const DEFAULT_POINTER: &str = ">";
 const DEFAULT_BORDER_LABEL: &str = "";
 const DEFAULT_HEADER: &str = "";
 
-#[derive(Debug, Builder)]
-/// Struct that represents the `fzf` program
+#[derive(Debug, Clone, Builder)]
+#[builder(build_fn(error = "FzfError", validate = "Self::validate"))]
+/// Struct that represents the configuration of the `fzf` program
+///
+/// The configuration can be run any number of times with the `run` method, each of which returns
+/// an [`FzfSession`] for the running instance of `fzf`
 pub struct Fzf {
-    #[builder(setter(skip))]
-    /// Contains the child stuct returned when the fzf command is spawned
-    instance: Option<Child>,
-    /// Contains the child process stdin handle after the `run` method has been called
-    #[builder(setter(skip))]
-    stdin: Option<ChildStdin>,
-
     /// Additional arguments that this library doesn't currently support as a predefined option
     #[builder(setter(custom), default = "vec![]")]
     custom_args: Vec<String>,
+    /// The `fzf` executable to run, such as a vendored path, `fzf-tmux` or a wrapper script
+    ///
+    /// Defaults to the [`crate::FZF_BIN_ENV`] environment variable, or `fzf` from `PATH`
+    #[builder(setter(into, strip_option), default = "None")]
+    executable: Option<PathBuf>,
+    /// What to do with options the installed version of `fzf` doesn't support
+    #[builder(setter(into, strip_option), default = "VersionPolicy::Ignore")]
+    version_policy: VersionPolicy,
+    /// How to treat the user's `FZF_DEFAULT_OPTS` and related environment variables
+    #[builder(setter(into, strip_option), default = "DefaultOptsPolicy::Inherit")]
+    default_opts: DefaultOptsPolicy,
+    /// Environment variables to set for `fzf`
+    #[builder(setter(custom), default = "vec![]")]
+    env: Vec<(OsString, OsString)>,
+    /// Environment variables to remove for `fzf`
+    #[builder(setter(custom), default = "vec![]")]
+    env_remove: Vec<OsString>,
+    /// The working directory to run `fzf` in
+    #[builder(setter(into, strip_option), default = "None")]
+    current_dir: Option<PathBuf>,
 
     // Search
     /// Scoring scheme
@@ -39,11 +105,51 @@ pub struct Fzf {
     /// Reverse the order of the input
     #[builder(setter(into, strip_option), default = "false")]
     tac: bool,
+    /// What separates the fields of each item, which are whitespace separated by default. Regular
+    /// expressions need the `regex` feature
+    #[builder(setter(into, strip_option), default = "None")]
+    delimiter: Option<Delimiter>,
+    /// Fields of the displayed text to search, instead of all of it
+    #[builder(setter(custom), default = "vec![]")]
+    nth: Vec<FieldRange>,
+    /// Fields of each item to display and search, instead of the whole item
+    #[builder(setter(custom), default = "vec![]")]
+    with_nth: Vec<FieldRange>,
     /// Do not perform search
     #[builder(setter(into, strip_option), default = "false")]
     disabled: bool,
 
     // Interface
+    /// Enable multi-select with tab/shift-tab
+    #[builder(setter(into, strip_option), default = "Multi::Disabled")]
+    multi: Multi,
+    /// The order multiple selections are returned in
+    #[builder(setter(into, strip_option), default = "MultiOrder::Picked")]
+    multi_order: MultiOrder,
+    /// Start `fzf` with the given query
+    #[builder(setter(into, strip_option), default = "None")]
+    query: Option<String>,
+    /// Automatically select the only match, without showing the ui
+    #[builder(setter(into, strip_option), default = "false")]
+    select_1: bool,
+    /// Exit immediately when there's no match, without showing the ui
+    #[builder(setter(into, strip_option), default = "false")]
+    exit_0: bool,
+    /// Print query as the first line of the output
+    #[builder(setter(into, strip_option), default = "false")]
+    print_query: bool,
+    /// Let enter accept the query when nothing matches it, printing the query as the output
+    #[builder(setter(into, strip_option), default = "false")]
+    accept_query: bool,
+    /// Fields of the selected items to print, instead of the whole item
+    #[builder(setter(custom), default = "vec![]")]
+    accept_nth: Vec<FieldRange>,
+    /// Keys that accept the selection, reporting which one was pressed in the output
+    #[builder(setter(custom), default = "vec![]")]
+    expect: Vec<Key>,
+    /// Keys and events bound to chains of actions
+    #[builder(setter(custom), default = "vec![]")]
+    binds: Vec<Binding>,
     /// Disable mouse
     #[builder(setter(into, strip_option), default = "false")]
     no_mouse: bool,
@@ -60,37 +166,141 @@ pub struct Fzf {
     #[builder(setter(into, strip_option), default = "false")]
     filepath_word: bool,
 
+    // Preview Options
+    /// Command to preview the focused item with, where `{}` is replaced by the item
+    #[builder(setter(into, strip_option), default = "None")]
+    preview: Option<String>,
+    /// Rust closure to preview the focused item with, which replaces the `preview` command
+    #[builder(setter(custom), default = "None")]
+    preview_fn: Option<Callback>,
+    /// Layout of the preview window
+    #[builder(setter(into, strip_option), default = "None")]
+    preview_window: Option<PreviewWindow>,
+    /// Label to print on the border of the preview window
+    #[builder(setter(into, strip_option), default = "None")]
+    preview_label: Option<String>,
+    /// Position of the preview window's border label
+    #[builder(setter(into, strip_option), default = "None")]
+    preview_label_pos: Option<LabelPosition>,
+
+    // Live Search Options
+    /// Rust closure returning the items matching the query, run again whenever it changes
+    #[builder(setter(custom), default = "None")]
+    live_search: Option<Callback>,
+    /// How long the query has to stay the same before the live search closure is run
+    #[builder(setter(into, strip_option), default = "DEFAULT_LIVE_SEARCH_DEBOUNCE")]
+    live_search_debounce: Duration,
+    /// Key that switches from the live search closure to `fzf` filtering the current results
+    #[builder(setter(into, strip_option), default = "None")]
+    live_search_toggle: Option<Key>,
+
+    // Remote Control Options
+    /// Start an http server that the session's [`crate::FzfClient`] sends requests to
+    #[cfg(feature = "listen")]
+    #[builder(setter(into, strip_option), default = "None")]
+    listen: Option<Listen>,
+    /// Secret that requests to the `listen` server must include
+    #[cfg(feature = "listen")]
+    #[builder(setter(into, strip_option), default = "None")]
+    listen_api_key: Option<String>,
+
     // Layout Options
     /// Choose layout
     #[builder(setter(into, strip_option), default = "Layout::Default")]
     layout: Layout,
+    /// Display `fzf` below the cursor with the given height, instead of using the whole screen
+    #[builder(setter(into, strip_option), default = "None")]
+    height: Option<Height>,
+    /// Minimum height in lines when `height` is given as a percentage (default: 10)
+    #[builder(setter(into, strip_option), default = "None")]
+    min_height: Option<u16>,
+    /// Space outside the border
+    #[builder(setter(into, strip_option), default = "None")]
+    margin: Option<Spacing>,
+    /// Space inside the border
+    #[builder(setter(into, strip_option), default = "None")]
+    padding: Option<Spacing>,
     /// Draw border around the finder
-    #[builder(setter(into, strip_option), default = "Border::None")]
-    border: Border,
+    #[builder(setter(into, strip_option), default = "None")]
+    border: Option<Border>,
     /// Label to print on the border
     #[builder(
         setter(into, strip_option),
         default = "DEFAULT_BORDER_LABEL.to_string()"
     )]
     border_label: String,
+    /// Position of the border label
+    #[builder(setter(into, strip_option), default = "None")]
+    border_label_pos: Option<LabelPosition>,
+    /// Preset for the look of the borders and sections, which the other border options refine
+    #[builder(setter(into, strip_option), default = "None")]
+    style: Option<Style>,
+    /// Draw border around the input section, with the prompt and info line
+    #[builder(setter(into, strip_option), default = "None")]
+    input_border: Option<Border>,
+    /// Label to print on the input border
+    #[builder(setter(into, strip_option), default = "None")]
+    input_label: Option<String>,
+    /// Position of the input border label
+    #[builder(setter(into, strip_option), default = "None")]
+    input_label_pos: Option<LabelPosition>,
+    /// Draw border around the list section, with the items
+    #[builder(setter(into, strip_option), default = "None")]
+    list_border: Option<Border>,
+    /// Label to print on the list border
+    #[builder(setter(into, strip_option), default = "None")]
+    list_label: Option<String>,
+    /// Position of the list border label
+    #[builder(setter(into, strip_option), default = "None")]
+    list_label_pos: Option<LabelPosition>,
+    /// Draw border around the header section
+    #[builder(setter(into, strip_option), default = "None")]
+    header_border: Option<Border>,
+    /// Label to print on the header border
+    #[builder(setter(into, strip_option), default = "None")]
+    header_label: Option<String>,
+    /// Position of the header border label
+    #[builder(setter(into, strip_option), default = "None")]
+    header_label_pos: Option<LabelPosition>,
+    /// Where to show the info line
+    #[builder(setter(into, strip_option), default = "Info::Default")]
+    info: Info,
+    /// String to draw the info line separator with, repeated to fill the line (default: '─')
+    #[builder(setter(into, strip_option), default = "None")]
+    separator: Option<String>,
     /// Hide info line separator
     #[builder(setter(into, strip_option), default = "false")]
     no_separator: bool,
+    /// Characters to draw the scrollbar with, the second for the preview window (default: '│')
+    #[builder(setter(into, strip_option), default = "None")]
+    scrollbar: Option<String>,
     /// Hide scrollbar
     #[builder(setter(into, strip_option), default = "false")]
     no_scrollbar: bool,
+    /// Number of lines to keep above and below the cursor when scrolling (default: 0)
+    #[builder(setter(into, strip_option), default = "None")]
+    scroll_off: Option<u16>,
     /// Input prompt (default: '> ')
     #[builder(setter(into, strip_option), default = "DEFAULT_PROMPT.to_string()")]
     prompt: String,
     /// Pointer to the current line (default: '>')
     #[builder(setter(into, strip_option), default = "DEFAULT_POINTER.to_string()")]
     pointer: String,
+    /// Marker for selected lines (default: '>')
+    #[builder(setter(into, strip_option), default = "None")]
+    marker: Option<String>,
+    /// Ellipsis to show when lines are cut off (default: '..')
+    #[builder(setter(into, strip_option), default = "None")]
+    ellipsis: Option<String>,
     /// String to print as header
     #[builder(setter(into, strip_option), default = "DEFAULT_HEADER.to_string()")]
     header: String,
     /// Print header before the prompt line
     #[builder(setter(into, strip_option), default = "false")]
     header_first: bool,
+    /// Use ASCII characters instead of Unicode box drawing characters
+    #[builder(setter(into, strip_option), default = "false")]
+    no_unicode: bool,
 
     // Display
     /// Enable processing of ANSI color codes
@@ -99,9 +309,10 @@ pub struct Fzf {
     /// Number of spaces for a tab character (default: 8)
     #[builder(setter(into, strip_option), default = "8")]
     tabstop: u8,
-    /// Base scheme (dark|light|16|bw)
-    #[builder(setter(into, strip_option), default = "Color::Dark")]
-    color: Color,
+    /// Color scheme, either a base theme (dark|light|16|bw) or a [`ColorScheme`] that draws parts
+    /// of the ui differently
+    #[builder(setter(into, strip_option), default = "ColorScheme::from(Color::Dark)")]
+    color: ColorScheme,
     /// Do not use bold text
     #[builder(setter(into, strip_option), default = "false")]
     no_bold: bool,
@@ -110,10 +321,194 @@ pub struct Fzf {
 impl FzfBuilder {
     /// Additional arguments that this library doesn't currently support as a predefined option
     #[allow(dead_code)]
-    pub fn custom_args(&mut self, args: impl IntoIterator<Item = impl Into<String>>) -> &mut Self{
+    pub fn custom_args(&mut self, args: impl IntoIterator<Item = impl Into<String>>) -> &mut Self {
         self.custom_args = Some(args.into_iter().map(|x| x.into()).collect());
         self
     }
+
+    /// Sets an environment variable for `fzf`, which can be called multiple times
+    pub fn env(&mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> &mut Self {
+        self.env
+            .get_or_insert_with(Vec::new)
+            .push((key.into(), value.into()));
+        self
+    }
+
+    /// Removes an environment variable for `fzf`, which can be called multiple times
+    pub fn env_remove(&mut self, key: impl Into<OsString>) -> &mut Self {
+        self.env_remove
+            .get_or_insert_with(Vec::new)
+            .push(key.into());
+        self
+    }
+
+    /// Fields of the displayed text to search, instead of all of it, such as `[2]` for only the
+    /// second field
+    pub fn nth<R: Into<FieldRange>>(&mut self, fields: impl IntoIterator<Item = R>) -> &mut Self {
+        self.nth = Some(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Fields of each item to display and search, instead of the whole item, such as `[2..]` to
+    /// hide the first field
+    pub fn with_nth<R: Into<FieldRange>>(
+        &mut self,
+        fields: impl IntoIterator<Item = R>,
+    ) -> &mut Self {
+        self.with_nth = Some(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Fields of the selected items to print, instead of the whole item, such as `[1]` to only get
+    /// back the first field
+    ///
+    /// This is ignored when selecting rust values, which are always returned whole. Building the
+    /// [`Fzf`] returns [`FzfError::Builder`] if it's combined with [`MultiOrder::Input`], as the
+    /// printed fields can't be matched back to the items to sort them
+    pub fn accept_nth<R: Into<FieldRange>>(
+        &mut self,
+        fields: impl IntoIterator<Item = R>,
+    ) -> &mut Self {
+        self.accept_nth = Some(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Keys that accept the selection, reporting which one was pressed in the output
+    ///
+    /// Building the [`Fzf`] returns [`FzfError::InvalidKey`] if any of them is an event
+    pub fn expect(&mut self, keys: impl IntoIterator<Item = Key>) -> &mut Self {
+        self.expect = Some(keys.into_iter().collect());
+        self
+    }
+
+    /// Rust closure to preview the focused item with, given the item's text and returning the text
+    /// to show in the preview window
+    ///
+    /// The closure only gets the text `fzf` shows, use [`crate::run_with_preview`] to preview
+    /// typed items. `fzf` runs the current executable to call the closure, so
+    /// [`crate::handle_self_invocation`] must be called at the start of `main`. Only available on
+    /// unix, as the command is quoted for a POSIX shell
+    #[cfg(unix)]
+    pub fn preview_fn(&mut self, f: impl Fn(&str) -> String + Send + Sync + 'static) -> &mut Self {
+        self.preview_fn = Some(Some(Callback::new(f)));
+        self
+    }
+
+    /// Rust closure returning the items matching the query, which is run again whenever the query
+    /// changes, in place of `fzf`'s own search
+    ///
+    /// The closure isn't run until the query has stayed the same for the
+    /// `live_search_debounce` duration, and results for an old query are thrown away. Items
+    /// added to the session are replaced by the closure's results as soon as `fzf` starts
+    ///
+    /// `fzf` runs the current executable to call the closure, so
+    /// [`crate::handle_self_invocation`] must be called at the start of `main`. The results are
+    /// lines of text, so it can't be used to select typed items with functions like
+    /// [`crate::run_with_selection`], which return [`FzfError::Builder`]. Only available on unix,
+    /// as the command is quoted for a POSIX shell
+    #[cfg(unix)]
+    pub fn live_search(
+        &mut self,
+        f: impl Fn(&str) -> Vec<String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        let search = move |query: &str| f(query).iter().map(|x| format!("{}\n", x)).collect();
+        self.live_search = Some(Some(Callback::new(search)));
+        self
+    }
+
+    /// Binds a key or event to a chain of actions, which can be called multiple times
+    ///
+    /// Binding the same key again replaces its actions. Options that need a key or event
+    /// themselves, such as `live_search` with the change event, run their actions first
+    ///
+    /// The binding is checked when the [`Fzf`] is built, returning [`FzfError::InvalidKey`] if
+    /// `fzf` has no name for the key, or [`FzfError::InvalidBind`] if it can't be passed to `fzf`
+    /// or binds enter along with the `accept_query` option
+    pub fn bind(&mut self, key: Key, actions: impl IntoIterator<Item = Action>) -> &mut Self {
+        self.binds.get_or_insert_with(Vec::new).push(Binding {
+            key,
+            actions: actions.into_iter().collect(),
+        });
+        self
+    }
+
+    /// Checks that every binding can be turned into a `--bind` argument
+    fn validate(&self) -> Result<(), FzfError> {
+        for binding in self.binds.iter().flatten() {
+            binding.key.validate()?;
+            binding.to_arg()?;
+        }
+        for key in self.expect.iter().flatten() {
+            key.validate()?;
+            if key.is_event() {
+                return Err(FzfError::InvalidKey(format!(
+                    "{} is an event, not a key",
+                    key
+                )));
+            }
+        }
+        if let Some(Some(key)) = self.live_search_toggle {
+            key.validate()?;
+        }
+        let binds_enter = self.binds.iter().flatten().any(|x| x.key == Key::Enter);
+        if self.accept_query == Some(true) && binds_enter {
+            return Err(FzfError::InvalidBind(
+                "enter can't be bound when the query can be accepted with it".to_string(),
+            ));
+        }
+        let accepts_fields = self.accept_nth.as_ref().is_some_and(|x| !x.is_empty());
+        if accepts_fields && self.multi_order == Some(MultiOrder::Input) {
+            // Selections are sorted by looking up the whole item, which `fzf` no longer prints
+            return Err(FzfError::Builder(
+                "accept_nth can't be used with the input multi order".to_string(),
+            ));
+        }
+        #[cfg(feature = "listen")]
+        if let Some(Some(listen)) = &self.listen {
+            listen.validate()?;
+        }
+        let fields = [&self.nth, &self.with_nth, &self.accept_nth];
+        if let Some(range) = fields
+            .into_iter()
+            .flatten()
+            .flatten()
+            .find(|x| x.has_zero())
+        {
+            return Err(FzfError::Builder(format!(
+                "field index expression {} uses 0, but fields are counted from 1",
+                range
+            )));
+        }
+        if self.border == Some(Some(Border::Line)) {
+            return Err(FzfError::Builder(
+                "the line border is only supported by the input, list and header borders"
+                    .to_string(),
+            ));
+        }
+        let label_positions = [
+            self.preview_label_pos,
+            self.border_label_pos,
+            self.input_label_pos,
+            self.list_label_pos,
+            self.header_label_pos,
+        ];
+        for position in label_positions.into_iter().flatten().flatten() {
+            position.validate()?;
+        }
+        let mut sizes: Vec<Size> = self
+            .height
+            .flatten()
+            .map(Height::size)
+            .into_iter()
+            .collect();
+        for spacing in [self.margin, self.padding].into_iter().flatten().flatten() {
+            sizes.extend(spacing.sizes());
+        }
+        for size in sizes {
+            size.validate()?;
+        }
+        Ok(())
+    }
 }
 
 impl Fzf {
@@ -123,132 +518,726 @@ impl Fzf {
     }
 
     /// Spawns `fzf` as a child proccess, and displays it to stdout
-    pub fn run(&mut self) -> io::Result<()> {
-        let args: Vec<String> = self
-            .get_fzf_args()
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfSession`] for the running instance of `fzf`, which items can be added to
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::NotInstalled`] if `fzf` could not be found, or [`FzfError::Spawn`] if
+    /// it could not be started for any other reason
+    pub fn run(&self) -> Result<FzfSession, FzfError> {
+        self.spawn(false)
+    }
+
+    /// Spawns `fzf` with the first tab separated field of each item hidden, so that items can be
+    /// prefixed with their index
+    pub(crate) fn run_indexed(&self) -> Result<FzfSession, FzfError> {
+        self.spawn(true)
+    }
+
+    /// Sets the closure to preview items with, replacing any set with the `preview_fn` option
+    #[cfg(unix)]
+    pub(crate) fn with_preview_fn(
+        mut self,
+        f: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.preview_fn = Some(Callback::new(f));
+        self
+    }
+
+    /// Spawns `fzf` with the configured arguments
+    fn spawn(&self, indexed: bool) -> Result<FzfSession, FzfError> {
+        let mut launch = self.launch(indexed)?;
+        let fzf = launch.command.spawn().map_err(FzfError::spawn)?;
+        let session = FzfSession::new(fzf, launch.format, launch.warnings, launch.callbacks);
+        #[cfg(feature = "listen")]
+        let session = session.with_client(launch.client);
+        Ok(session)
+    }
+
+    /// Spawns `fzf` as a child proccess using `tokio::process`, and displays it to stdout
+    ///
+    /// # Returns
+    ///
+    /// An [`AsyncFzfSession`] for the running instance of `fzf`, which items can be added to
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `run` method
+    #[cfg(feature = "tokio")]
+    pub async fn run_async(&self) -> Result<AsyncFzfSession, FzfError> {
+        let executable = self.executable()?;
+        let version = match self.version_policy {
+            VersionPolicy::Ignore => None,
+            _ => Some(version::probe_async(&executable).await?),
+        };
+        let launch = self.launch_with(false, executable, version)?;
+        let fzf = tokio::process::Command::from(launch.command)
+            .spawn()
+            .map_err(FzfError::spawn)?;
+        let session = AsyncFzfSession::new(fzf, launch.format, launch.warnings, launch.callbacks);
+        #[cfg(feature = "listen")]
+        let session = session.with_client(launch.client);
+        Ok(session)
+    }
+
+    /// Gets the version of the `fzf` executable this configuration runs
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::NotInstalled`] if `fzf` could not be found, or
+    /// [`FzfError::UnknownVersion`] if its version could not be parsed
+    pub fn version(&self) -> Result<FzfVersion, FzfError> {
+        version::probe(&self.executable()?)
+    }
+
+    /// Describes the output `fzf` will print with this configuration
+    fn output_format(&self, indexed: bool) -> OutputFormat {
+        OutputFormat {
+            indexed,
+            multi_order: self.multi_order,
+            start_marker: None,
+            print_query: self.print_query || self.accept_query,
+            expect: self.expect.clone(),
+            delimiter: self.delimiter.clone(),
+        }
+    }
+
+    /// Ranks the given items against the query without showing the ui, using `fzf`'s filter mode
+    ///
+    /// Only the search options, such as `scheme`, `literal` and `tac`, are used, along with any
+    /// custom arguments. This doesn't need a terminal, so it works in CI and batch commands
+    ///
+    /// **NOTE: Blocks execution until `fzf` has ranked every item**
+    ///
+    /// # Parameters
+    ///
+    /// - `items` The items to rank
+    /// - `query` The query to rank the items against
+    ///
+    /// # Returns
+    ///
+    /// The items matching the query, best match first
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `run` method, or [`FzfError::ExitCode`] if `fzf` failed
+    pub fn filter(
+        &self,
+        items: impl IntoIterator<Item = impl Into<String>>,
+        query: &str,
+    ) -> Result<Vec<String>, FzfError> {
+        let lines = items.into_iter().map(Into::into).collect();
+        self.filter_lines(lines, query, false)
+    }
+
+    /// Ranks the given rust values against the query without showing the ui, using `fzf`'s
+    /// filter mode
+    ///
+    /// Works the same as the `filter` method, with each value displayed using [`FzfItem`]
+    ///
+    /// # Parameters
+    ///
+    /// - `items` The values to rank
+    /// - `query` The query to rank the values against
+    ///
+    /// # Returns
+    ///
+    /// The values matching the query, best match first
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `filter` method
+    pub fn filter_items<T: FzfItem>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        query: &str,
+    ) -> Result<Vec<T>, FzfError> {
+        let items: Vec<T> = items.into_iter().collect();
+        let lines = items
             .iter()
-            .chain(self.custom_args.iter())
-            .map(|x| x.to_owned())
+            .enumerate()
+            .map(|(i, item)| item::indexed_line(i, &item.fzf_text()))
+            .collect();
+        let matches = self.filter_lines(lines, query, true)?;
+        let indexes = matches.iter().filter_map(|x| item::parse_index(x));
+        Ok(item::take_items(items, indexes))
+    }
+
+    /// Runs `fzf` in filter mode with the given lines
+    ///
+    /// Options dropped by [`VersionPolicy::Warn`] aren't reported, as there is no session
+    fn filter_lines(
+        &self,
+        lines: Vec<String>,
+        query: &str,
+        indexed: bool,
+    ) -> Result<Vec<String>, FzfError> {
+        let executable = self.executable()?;
+        let version = self.checked_version(&executable)?;
+        let args = self
+            .default_opts_resets()
+            .into_iter()
+            .chain(self.field_args(indexed))
+            .chain(self.search_args())
+            .chain([format!("--filter={}", query)])
             .collect();
+        let (args, _) = version::check_args(args, version, self.version_policy)?;
+        let fzf = self
+            .command(executable, args)
+            .spawn()
+            .map_err(FzfError::spawn)?;
+        let format = OutputFormat {
+            indexed,
+            ..Default::default()
+        };
+        let mut session = FzfSession::new(fzf, format, vec![], None);
+        // Fed from a thread, as `fzf` may start printing matches before it has read every item
+        session.feed(lines)?;
+        session.output_filtered()
+    }
+
+    /// Prepares everything needed to spawn `fzf` with the configured arguments, checking them
+    /// against the installed version of `fzf` if the version policy asks for it
+    fn launch(&self, indexed: bool) -> Result<Launch, FzfError> {
+        if indexed && self.live_search.is_some() {
+            // The closure's results don't have the index the selection is found with
+            return Err(FzfError::Builder(
+                "live_search can't be used to select typed items".to_string(),
+            ));
+        }
+        let executable = self.executable()?;
+        let version = self.checked_version(&executable)?;
+        self.launch_with(indexed, executable, version)
+    }
+
+    /// Prepares everything needed to spawn the given `fzf` executable, checking the arguments
+    /// against its version if there is one
+    fn launch_with(
+        &self,
+        indexed: bool,
+        executable: PathBuf,
+        version: Option<FzfVersion>,
+    ) -> Result<Launch, FzfError> {
+        let mut format = self.output_format(indexed);
+        // Without the start event there's no way to tell if the ui was skipped
+        if (self.select_1 || self.exit_0) && version.is_none_or(|x| x >= version::START_EVENT) {
+            format.start_marker = StartMarker::create().map_err(FzfError::Spawn)?;
+        }
+
+        let mut args = self.default_opts_resets();
+        args.extend(self.field_args(indexed));
+        if !indexed && !self.accept_nth.is_empty() {
+            // Selected rust values are found by their index, so they're always printed whole
+            args.push(format!("--accept-nth={}", field_list(&self.accept_nth)));
+        }
+        let mut callbacks = vec![];
+
+        let mut binds = vec![];
+        let mut start = vec![];
+        if let Some(marker) = &format.start_marker {
+            // `fzf` doesn't fire the start event when it skips the ui, so the marker tells us
+            // whether the user saw it
+            let touch = format!("touch {}", shell_quote(&marker.path().to_string_lossy()));
+            start.push(Action::ExecuteSilent(touch));
+        }
+        if let Some(search) = &self.live_search {
+            let reload = Action::Reload(CallbackServer::command("search", "{q}")?);
+            start.push(reload.clone());
+            args.push("--disabled".to_string());
+            binds.push(Binding {
+                key: Key::Change,
+                actions: vec![reload],
+            });
+            if let Some(key) = self.live_search_toggle {
+                let actions = vec![
+                    Action::Unbind(vec![Key::Change, key]),
+                    Action::EnableSearch,
+                    Action::ClearQuery,
+                ];
+                binds.push(Binding { key, actions });
+            }
+            let search = search.clone().debounced(self.live_search_debounce);
+            callbacks.push(("search", search));
+        }
+        if !start.is_empty() {
+            binds.push(Binding {
+                key: Key::Start,
+                actions: start,
+            });
+        }
+        args.extend(self.get_fzf_args());
+        args.extend(self.bind_args(binds)?);
+
+        if let Some(preview) = &self.preview_fn {
+            // Comes after the `preview` option, so it takes its place
+            args.push(format!(
+                "--preview={}",
+                CallbackServer::command("preview", "{}")?
+            ));
+            callbacks.push(("preview", preview.clone()));
+        }
+        #[cfg(feature = "listen")]
+        let client = match &self.listen {
+            Some(listen) => {
+                let (arg, client) = listen.launch(self.listen_api_key.clone())?;
+                args.push(arg);
+                Some(client)
+            }
+            None => None,
+        };
+        let (args, warnings) = version::check_args(args, version, self.version_policy)?;
+
+        let mut command = self.command(executable, args);
+        #[cfg(feature = "listen")]
+        if let Some(key) = &self.listen_api_key {
+            command.env(listen::API_KEY_ENV, key);
+        }
+        let callbacks = match callbacks.is_empty() {
+            true => None,
+            false => Some(CallbackServer::spawn(callbacks)?),
+        };
+        if let Some(server) = &callbacks {
+            command.envs(server.env());
+        }
+
+        Ok(Launch {
+            command,
+            format,
+            warnings,
+            callbacks,
+            #[cfg(feature = "listen")]
+            client,
+        })
+    }
+
+    /// Gets the `fzf` executable to run
+    ///
+    /// Returns [`FzfError::NotInstalled`] if no `fzf` executable could be found
+    fn executable(&self) -> Result<PathBuf, FzfError> {
+        executable::resolve(self.executable.as_deref()).ok_or(FzfError::NotInstalled)
+    }
+
+    /// Gets the version of `fzf` to check options against, or `None` if the version policy
+    /// ignores it
+    fn checked_version(&self, executable: &Path) -> Result<Option<FzfVersion>, FzfError> {
+        match self.version_policy {
+            VersionPolicy::Ignore => Ok(None),
+            _ => version::probe(executable).map(Some),
+        }
+    }
 
-        let mut fzf = Command::new("fzf")
+    /// Creates the command to spawn `fzf` with the given arguments followed by the custom
+    /// arguments, with its stdio piped and its environment set up
+    fn command(&self, executable: PathBuf, args: Vec<String>) -> Command {
+        let mut command = Command::new(executable);
+        command
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
             .args(args)
-            .spawn()?;
+            .args(&self.custom_args);
 
-        self.stdin = fzf.stdin.take();
-        self.instance = Some(fzf);
-        Ok(())
+        if self.default_opts == DefaultOptsPolicy::Ignore {
+            for key in DEFAULT_OPTS_ENV {
+                command.env_remove(key);
+            }
+        }
+        for key in &self.env_remove {
+            command.env_remove(key);
+        }
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+        command
     }
 
-    /// Creates the vec of arguments to pass to `fzf`
-    fn get_fzf_args(&self) -> Vec<String> {
+    /// Creates the arguments that undo the user's default options that would change what `fzf`
+    /// prints, when merging with them
+    ///
+    /// These come before every other argument, so the configured options still apply. The field
+    /// options are found by splitting the defaults into words the way a shell would, without
+    /// knowing which options take a value. A value given as its own word that looks like one of
+    /// them, such as `--header --nth`, resets that option needlessly, which is harmless
+    fn default_opts_resets(&self) -> Vec<String> {
+        if self.default_opts != DefaultOptsPolicy::Merge {
+            return vec![];
+        }
+        let mut args: Vec<String> = DEFAULT_OPTS_RESETS.iter().map(|x| x.to_string()).collect();
+
+        let defaults = shell_words(&self.user_default_opts());
+        for (long, short, reset) in FIELD_OPTS_RESETS {
+            let is_set = defaults.iter().any(|x| {
+                x == long
+                    || x.strip_prefix(long).is_some_and(|x| x.starts_with('='))
+                    || short.is_some_and(|short| x.starts_with(short))
+            });
+            if is_set {
+                args.push(reset.to_string());
+            }
+        }
+        args
+    }
+
+    /// Gets the user's default options that `fzf` will read, from the file named by
+    /// `FZF_DEFAULT_OPTS_FILE` followed by `FZF_DEFAULT_OPTS`, taking the configured environment
+    /// into account
+    fn user_default_opts(&self) -> String {
+        let var = |key: &str| match self.env.iter().rev().find(|(name, _)| name == key) {
+            Some((_, value)) => Some(value.clone()),
+            None if self.env_remove.iter().any(|x| x == key) => None,
+            None => env::var_os(key),
+        };
+
+        let mut defaults = String::new();
+        if let Some(path) = var("FZF_DEFAULT_OPTS_FILE") {
+            let path = match &self.current_dir {
+                Some(dir) => dir.join(path),
+                None => PathBuf::from(path),
+            };
+            defaults.push_str(&fs::read_to_string(path).unwrap_or_default());
+            defaults.push('\n');
+        }
+        if let Some(opts) = var("FZF_DEFAULT_OPTS") {
+            defaults.push_str(&opts.to_string_lossy());
+        }
+        defaults
+    }
+
+    /// Creates the arguments that split items into fields, hiding the leading index field of items
+    /// created with [`item::indexed_line`]
+    ///
+    /// `nth` applies to the fields `with_nth` displays, so only `with_nth` is moved past the index
+    /// field
+    fn field_args(&self, indexed: bool) -> Vec<String> {
         let mut args = vec![];
+        let uses_fields = self.delimiter.is_some() || !self.nth.is_empty();
 
-        /// Adds the option if the value is true
-        fn add_if_true<T: Into<String>>(args: &mut Vec<String>, fzf_arg: T, value: bool) {
-            if value {
-                args.push(fzf_arg.into());
+        match (indexed, &self.delimiter) {
+            (true, _) if !uses_fields && self.with_nth.is_empty() => {
+                args.push("--delimiter=\t".to_string());
             }
+            // The anchored alternative only matches the index field at the start of each item
+            (true, delimiter) => {
+                let pattern = delimiter
+                    .as_ref()
+                    .map_or(r"\s+".to_string(), Delimiter::pattern);
+                args.push(format!("--delimiter=^[0-9]+\t|(?:{})", pattern));
+            }
+            (false, Some(delimiter)) => args.push(format!("--delimiter={}", delimiter.pattern())),
+            (false, None) => (),
+        }
+        if !self.nth.is_empty() {
+            args.push(format!("--nth={}", field_list(&self.nth)));
+        }
+        let with_nth = match indexed {
+            true if self.with_nth.is_empty() => vec![FieldRange::From(2)],
+            true => self.with_nth.iter().map(|x| x.shifted(1)).collect(),
+            false => self.with_nth.clone(),
+        };
+        if !with_nth.is_empty() {
+            args.push(format!("--with-nth={}", field_list(&with_nth)));
         }
 
-        // Search
-        args.push(format!("--scheme={}", self.scheme.to_string()));
+        args
+    }
+
+    /// Creates the vec of arguments that change how `fzf` matches and ranks items, which are
+    /// shared with filter mode
+    fn search_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        if self.scheme != Scheme::Default {
+            args.push(format!("--scheme={}", self.scheme));
+        }
         add_if_true(&mut args, "--literal", self.literal);
-        add_if_true(&mut args, "--track", self.track);
         add_if_true(&mut args, "--tac", self.tac);
+
+        args
+    }
+
+    /// Creates the `--bind` arguments, with the bindings this crate needs merged into the
+    /// configured ones
+    fn bind_args(&self, mut needed: Vec<Binding>) -> Result<Vec<String>, FzfError> {
+        if self.accept_query {
+            needed.push(Binding {
+                key: Key::Enter,
+                actions: vec![Action::AcceptOrPrintQuery],
+            });
+        }
+        action::merge(needed, &self.binds)
+            .iter()
+            .map(Binding::to_arg)
+            .collect()
+    }
+
+    /// Creates the vec of arguments to pass to `fzf`, apart from the bindings
+    fn get_fzf_args(&self) -> Vec<String> {
+        let mut args = self.search_args();
+
+        // Search
+        add_if_true(&mut args, "--track", self.track);
         add_if_true(&mut args, "--disabled", self.disabled);
+        if let Some(query) = &self.query {
+            args.push(format!("--query={}", query));
+        }
+        add_if_true(&mut args, "--select-1", self.select_1);
+        add_if_true(&mut args, "--exit-0", self.exit_0);
 
         // Interface
+        match self.multi {
+            Multi::Disabled => (),
+            Multi::Unlimited => args.push("--multi".to_string()),
+            Multi::Limit(limit) => args.push(format!("--multi={}", limit)),
+        }
+        add_if_true(
+            &mut args,
+            "--print-query",
+            self.print_query || self.accept_query,
+        );
+        if !self.expect.is_empty() {
+            args.push(format!("--expect={}", key::key_list(&self.expect)));
+        }
         add_if_true(&mut args, "--no-mouse", self.no_mouse);
         add_if_true(&mut args, "--cycle", self.cycle);
         add_if_true(&mut args, "--keep-right", self.keep_right);
         add_if_true(&mut args, "--no-hscroll", self.no_hscroll);
         add_if_true(&mut args, "--filepath-word", self.filepath_word);
 
+        // Preview
+        if let Some(preview) = &self.preview {
+            args.push(format!("--preview={}", preview));
+        }
+        if let Some(window) = &self.preview_window {
+            args.push(format!("--preview-window={}", window));
+        }
+        add_if_some(&mut args, "--preview-label", &self.preview_label);
+        add_if_some(&mut args, "--preview-label-pos", &self.preview_label_pos);
+
         // Layout
-        args.push(format!("--layout={}", self.layout.to_string()));
-        args.push(format!("--border={}", self.border.to_string()));
-        args.push(format!("--border-label={}", self.border_label));
+        args.push(format!("--layout={}", self.layout));
+        if let Some(height) = self.height {
+            args.push(format!("--height={}", height));
+        }
+        if let Some(min_height) = self.min_height {
+            args.push(format!("--min-height={}", min_height));
+        }
+        if let Some(margin) = self.margin {
+            args.push(format!("--margin={}", margin));
+        }
+        if let Some(padding) = self.padding {
+            args.push(format!("--padding={}", padding));
+        }
+        // The style comes first, so that the other border options refine it
+        add_if_some(&mut args, "--style", &self.style);
+        add_if_some(&mut args, "--border", &self.border);
+        if !self.border_label.is_empty() {
+            args.push(format!("--border-label={}", self.border_label));
+        }
+        add_if_some(&mut args, "--border-label-pos", &self.border_label_pos);
+        add_if_some(&mut args, "--input-border", &self.input_border);
+        add_if_some(&mut args, "--input-label", &self.input_label);
+        add_if_some(&mut args, "--input-label-pos", &self.input_label_pos);
+        add_if_some(&mut args, "--list-border", &self.list_border);
+        add_if_some(&mut args, "--list-label", &self.list_label);
+        add_if_some(&mut args, "--list-label-pos", &self.list_label_pos);
+        add_if_some(&mut args, "--header-border", &self.header_border);
+        add_if_some(&mut args, "--header-label", &self.header_label);
+        add_if_some(&mut args, "--header-label-pos", &self.header_label_pos);
+        if self.info != Info::Default {
+            args.push(format!("--info={}", self.info));
+        }
+        if let Some(separator) = &self.separator {
+            args.push(format!("--separator={}", separator));
+        }
         add_if_true(&mut args, "--no-separator", self.no_separator);
+        if let Some(scrollbar) = &self.scrollbar {
+            args.push(format!("--scrollbar={}", scrollbar));
+        }
         add_if_true(&mut args, "--no-scrollbar", self.no_scrollbar);
+        if let Some(scroll_off) = self.scroll_off {
+            args.push(format!("--scroll-off={}", scroll_off));
+        }
         args.push(format!("--prompt={}", self.prompt));
         args.push(format!("--pointer={}", self.pointer));
+        if let Some(marker) = &self.marker {
+            args.push(format!("--marker={}", marker));
+        }
+        if let Some(ellipsis) = &self.ellipsis {
+            args.push(format!("--ellipsis={}", ellipsis));
+        }
         if !self.header.is_empty() {
             args.push(format!("--header={}", &self.header));
         }
         add_if_true(&mut args, "--header-first", self.header_first);
+        add_if_true(&mut args, "--no-unicode", self.no_unicode);
 
         // Display
         add_if_true(&mut args, "--ansi", self.ansi);
         args.push(format!("--tabstop={}", self.tabstop));
-        args.push(format!("--color={}", self.color.to_string()));
+        args.push(format!("--color={}", self.color));
         add_if_true(&mut args, "--no-bold", self.no_bold);
 
         args
     }
+}
 
-    /// Adds an item to the `fzf` selection ui
-    ///
-    /// # Parameters
-    ///
-    /// - `item` The item to add
-    ///
-    /// # Panics
-    ///
-    /// This function uses `expect()`, which will panic if the `run` method has not been called
-    /// before adding items
-    pub fn add_item<T: Into<String>>(&mut self, item: T) -> io::Result<()> {
-        // Trimming the string to make sure we don't double up on newline characters
-        let mut item = item.into().trim().to_string();
-        item.push('\n');
-        self.stdin
-            .as_mut()
-            .expect("Failed to unwrap stdin")
-            .write_all(item.as_bytes())?;
-        Ok(())
+impl Default for Fzf {
+    /// Creates the same configuration as building an empty [`FzfBuilder`]
+    fn default() -> Self {
+        Fzf {
+            custom_args: vec![],
+            executable: None,
+            version_policy: VersionPolicy::Ignore,
+            default_opts: DefaultOptsPolicy::Inherit,
+            env: vec![],
+            env_remove: vec![],
+            current_dir: None,
+            scheme: Scheme::Default,
+            literal: false,
+            track: false,
+            tac: false,
+            delimiter: None,
+            nth: vec![],
+            with_nth: vec![],
+            disabled: false,
+            multi: Multi::Disabled,
+            multi_order: MultiOrder::Picked,
+            query: None,
+            select_1: false,
+            exit_0: false,
+            print_query: false,
+            accept_query: false,
+            accept_nth: vec![],
+            expect: vec![],
+            binds: vec![],
+            no_mouse: false,
+            cycle: false,
+            keep_right: false,
+            no_hscroll: false,
+            filepath_word: false,
+            preview: None,
+            preview_fn: None,
+            preview_window: None,
+            preview_label: None,
+            preview_label_pos: None,
+            live_search: None,
+            live_search_debounce: DEFAULT_LIVE_SEARCH_DEBOUNCE,
+            live_search_toggle: None,
+            #[cfg(feature = "listen")]
+            listen: None,
+            #[cfg(feature = "listen")]
+            listen_api_key: None,
+            layout: Layout::Default,
+            height: None,
+            min_height: None,
+            margin: None,
+            padding: None,
+            border: None,
+            border_label: DEFAULT_BORDER_LABEL.to_string(),
+            border_label_pos: None,
+            style: None,
+            input_border: None,
+            input_label: None,
+            input_label_pos: None,
+            list_border: None,
+            list_label: None,
+            list_label_pos: None,
+            header_border: None,
+            header_label: None,
+            header_label_pos: None,
+            info: Info::Default,
+            separator: None,
+            no_separator: false,
+            scrollbar: None,
+            no_scrollbar: false,
+            scroll_off: None,
+            prompt: DEFAULT_PROMPT.to_string(),
+            pointer: DEFAULT_POINTER.to_string(),
+            marker: None,
+            ellipsis: None,
+            header: DEFAULT_HEADER.to_string(),
+            header_first: false,
+            no_unicode: false,
+            ansi: false,
+            tabstop: 8,
+            color: ColorScheme::from(Color::Dark),
+            no_bold: false,
+        }
     }
+}
 
-    /// Adds all the items in the given vec to the `fzf` selection ui
-    ///
-    /// Essentially a wrapper for `add_item` for convenience
-    ///
-    /// # Parameters
-    ///
-    /// - `items` The items to add
-    ///
-    /// # Panics
-    ///
-    /// This function calls `add_item`, which will panic if the `run` method has not been called
-    /// before adding items
-    pub fn add_items(&mut self, items: impl IntoIterator<Item = impl Into<String>>) -> io::Result<()> {
-        for item in items.into_iter() {
-            self.add_item(item)?;
-        }
-        Ok(())
+/// Everything needed to spawn `fzf` and make sense of its output
+struct Launch {
+    /// The command to spawn `fzf` with
+    command: Command,
+    /// Describes the output `fzf` will print
+    format: OutputFormat,
+    /// A warning for each option that was dropped because of the version policy
+    warnings: Vec<String>,
+    /// The server answering the callbacks of commands run by `fzf`, if any closures were given
+    callbacks: Option<CallbackServer>,
+    /// The client for the `listen` server, if the option was set
+    #[cfg(feature = "listen")]
+    client: Option<FzfClient>,
+}
+
+/// Adds the option if the value is true
+fn add_if_true<T: Into<String>>(args: &mut Vec<String>, fzf_arg: T, value: bool) {
+    if value {
+        args.push(fzf_arg.into());
     }
+}
 
-    /// Gets the output of `fzf`.
-    ///
-    /// The stdout of fzf is converted to a string using `from_utf8_lossy`
-    ///
-    /// **NOTE: Blocks execution until output is received**
-    ///
-    /// # Returns
-    ///
-    /// An option containing the users selection as a [`String`], or `None` if the user quit `fzf`
-    pub fn output(self) -> Option<String> {
-        drop(self.stdin);
-        let output = self.instance?.wait_with_output().ok()?;
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+/// Adds the option with the given value, if there is one
+fn add_if_some<T: fmt::Display>(args: &mut Vec<String>, fzf_arg: &str, value: &Option<T>) {
+    if let Some(value) = value {
+        args.push(format!("{}={}", fzf_arg, value));
     }
 }
 
-impl Default for Fzf {
-    fn default() -> Self {
-        FzfBuilder::default().build().unwrap()
+/// Splits the given string into words the way a POSIX shell would, handling quotes and
+/// backslashes, which is how `fzf` reads the user's default options
+fn shell_words(value: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                word.extend(chars.by_ref().take_while(|x| *x != '\''));
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some(next @ ('"' | '\\' | '$' | '`')) => word.push(next),
+                            Some(next) => word.extend(['\\', next]),
+                            None => word.push('\\'),
+                        },
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => word.get_or_insert_with(String::new).extend(chars.next()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
     }
+    words.extend(word);
+    words
+}
+
+/// Quotes the given string for the POSIX shell `fzf` runs commands with on unix
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
 }
 
 #[cfg(test)]
@@ -259,4 +1248,600 @@ mod tests {
     fn builder_works_as_expected() {
         let _ = Fzf::builder().build();
     }
+
+    #[test]
+    fn default_matches_builder_defaults() {
+        let args = Fzf::default().get_fzf_args();
+
+        assert_eq!(
+            format!("{:?}", Fzf::default()),
+            format!("{:?}", Fzf::builder().build().unwrap())
+        );
+        assert_eq!(args, Fzf::builder().build().unwrap().get_fzf_args());
+        assert!(args.contains(&"--prompt=> ".to_string()));
+        assert!(args.contains(&"--tabstop=8".to_string()));
+        assert_eq!(
+            Fzf::default().live_search_debounce,
+            DEFAULT_LIVE_SEARCH_DEBOUNCE
+        );
+    }
+
+    #[test]
+    fn multi_is_passed_to_fzf() {
+        let args = |multi: Multi| Fzf::builder().multi(multi).build().unwrap().get_fzf_args();
+
+        assert!(!args(Multi::Disabled)
+            .iter()
+            .any(|x| x.starts_with("--multi")));
+        assert!(args(Multi::Unlimited).contains(&"--multi".to_string()));
+        assert!(args(Multi::Limit(3)).contains(&"--multi=3".to_string()));
+    }
+
+    #[test]
+    fn sizes_and_spacing_are_passed() {
+        let args = Fzf::builder()
+            .height(Height::Adaptive(Size::Percent(40)))
+            .margin((1, Size::Percent(5)))
+            .padding(Size::Lines(1))
+            .info(Info::InlineRight)
+            .build()
+            .unwrap()
+            .get_fzf_args();
+
+        assert!(args.contains(&"--height=~40%".to_string()));
+        assert!(args.contains(&"--margin=1,5%".to_string()));
+        assert!(args.contains(&"--padding=1".to_string()));
+        assert!(args.contains(&"--info=inline-right".to_string()));
+        assert!(!Fzf::default()
+            .get_fzf_args()
+            .iter()
+            .any(|x| x.starts_with("--info")));
+
+        assert!(Fzf::builder()
+            .height(Height::Fixed(Size::Percent(100)))
+            .build()
+            .is_ok());
+        assert!(Fzf::builder()
+            .height(Height::Adaptive(Size::Percent(101)))
+            .build()
+            .is_err());
+        assert!(Fzf::builder()
+            .margin((1, Size::Percent(150)))
+            .build()
+            .is_err());
+        assert!(Fzf::builder().padding(Size::Percent(255)).build().is_err());
+    }
+
+    #[test]
+    fn borders_and_labels_are_passed() {
+        let args = Fzf::builder()
+            .border(Border::Double)
+            .border_label_pos(LabelPosition::bottom(LabelAlign::Right(2)))
+            .style(Style::Minimal)
+            .input_border(Border::Line)
+            .list_label("Colours")
+            .list_label_pos(LabelAlign::Left(3))
+            .build()
+            .unwrap()
+            .get_fzf_args();
+
+        let position = |arg: &str| args.iter().position(|x| x == arg).unwrap();
+        assert!(position("--style=minimal") < position("--border=double"));
+        assert!(args.contains(&"--border-label-pos=-2:bottom".to_string()));
+        assert!(args.contains(&"--input-border=line".to_string()));
+        assert!(args.contains(&"--list-label=Colours".to_string()));
+        assert!(args.contains(&"--list-label-pos=3".to_string()));
+        assert!(!args.iter().any(|x| x.starts_with("--header-border")));
+
+        let style_only = Fzf::builder().style(Style::Full).build().unwrap();
+        assert!(!style_only
+            .get_fzf_args()
+            .iter()
+            .any(|x| x.starts_with("--border")));
+        let no_border = Fzf::builder().border(Border::None).build().unwrap();
+        assert!(no_border
+            .get_fzf_args()
+            .contains(&"--border=none".to_string()));
+
+        assert!(Fzf::builder().border(Border::Line).build().is_err());
+        assert!(Fzf::builder()
+            .list_label_pos(LabelAlign::Left(0))
+            .build()
+            .is_err());
+        let bottom = LabelPosition::bottom(LabelAlign::Right(0));
+        assert!(Fzf::builder().preview_label_pos(bottom).build().is_err());
+        assert!(Fzf::builder()
+            .header_label_pos(LabelAlign::Center)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn index_field_is_hidden_around_the_configured_fields() {
+        let fzf = Fzf::builder()
+            .delimiter("\t")
+            .nth([2])
+            .with_nth([FieldRange::from(2..), FieldRange::from(..=-2)])
+            .accept_nth([1])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            fzf.field_args(false),
+            vec!["--delimiter=\t", "--nth=2", "--with-nth=2..,..-2"]
+        );
+        assert_eq!(
+            fzf.field_args(true),
+            vec![
+                "--delimiter=^[0-9]+\t|(?:\t)",
+                "--nth=2",
+                "--with-nth=3..,2..-2"
+            ]
+        );
+        assert_eq!(
+            Fzf::default().field_args(true),
+            vec!["--delimiter=\t", "--with-nth=2.."]
+        );
+        assert!(Fzf::builder().nth([0]).build().is_err());
+        assert!(matches!(
+            Fzf::builder()
+                .accept_nth([1])
+                .multi_order(MultiOrder::Input)
+                .build(),
+            Err(FzfError::Builder(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn accepted_fields_are_returned_in_the_order_they_were_picked() {
+        // Prints the first field of the second item, then the first, like `--accept-nth=1`
+        let script = r#"case "$*" in *--accept-nth=1*) ;; *) exit 2 ;; esac
+cat > /dev/null
+printf 'green\nred\n'"#;
+        let stand_in = executable::stand_in("accept-nth", script);
+        let fzf = Fzf::builder()
+            .executable(stand_in.path())
+            .delimiter(":")
+            .accept_nth([1])
+            .multi(Multi::Unlimited)
+            .multi_order(MultiOrder::Picked)
+            .build()
+            .unwrap();
+        let mut session = fzf.run().unwrap();
+        session.add_items(["red:1", "green:2"]).unwrap();
+
+        assert_eq!(
+            session.output_multi().unwrap(),
+            crate::FzfOutcome::Selected(vec!["green".to_string(), "red".to_string()])
+        );
+    }
+
+    #[test]
+    fn literal_delimiters_are_quoted() {
+        let fzf = Fzf::builder().delimiter("a.b|c").build().unwrap();
+        assert_eq!(fzf.field_args(false), vec![r"--delimiter=a\.b\|c"]);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regex_delimiters_use_go_syntax() {
+        let delimiter = Delimiter::regex(r"[:/]\s*").unwrap();
+        let fzf = Fzf::builder().delimiter(delimiter).build().unwrap();
+        assert_eq!(fzf.field_args(false), vec![r"--delimiter=[:/]\s*"]);
+
+        for pattern in [
+            "[a",
+            r"[a-z--c]",
+            r"[[:alpha:][a]]",
+            r"\b{start}",
+            "(?x)a",
+            r"\u00e9",
+        ] {
+            assert!(Delimiter::regex(pattern).is_err(), "{}", pattern);
+        }
+        assert!(Delimiter::regex(r"(?i)[[:alpha:]]\x{e9}\bé").is_ok());
+    }
+
+    #[test]
+    fn filter_only_uses_search_args() {
+        let fzf = Fzf::builder()
+            .literal(true)
+            .tac(true)
+            .no_mouse(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(fzf.search_args(), vec!["--literal", "--tac"]);
+        assert!(fzf.get_fzf_args().starts_with(&fzf.search_args()));
+    }
+
+    #[test]
+    fn field_options_in_the_users_defaults_are_reset() {
+        let file = env::temp_dir().join(format!("fzf-wrapped-opts-{}", std::process::id()));
+        fs::write(&file, "--with-nth 2\n--tac").unwrap();
+        let fzf = Fzf::builder()
+            .default_opts(DefaultOptsPolicy::Merge)
+            .env("FZF_DEFAULT_OPTS_FILE", &file)
+            .env("FZF_DEFAULT_OPTS", "--disabled -n2 --accept-nth=1")
+            .build()
+            .unwrap();
+        let resets = fzf.default_opts_resets();
+        fs::remove_file(&file).unwrap();
+
+        assert!(resets.starts_with(&DEFAULT_OPTS_RESETS.map(String::from)));
+        let fields = ["--nth=..", "--with-nth=..", "--accept-nth=.."].map(String::from);
+        assert!(resets.ends_with(&fields));
+
+        let fzf = Fzf::builder()
+            .default_opts(DefaultOptsPolicy::Merge)
+            .env("FZF_DEFAULT_OPTS", "--disabled --delimiter=:")
+            .env_remove("FZF_DEFAULT_OPTS_FILE")
+            .build()
+            .unwrap();
+        assert_eq!(
+            fzf.default_opts_resets().last().unwrap(),
+            "--delimiter=[ \t]+"
+        );
+        assert_eq!(
+            fzf.default_opts_resets().len(),
+            DEFAULT_OPTS_RESETS.len() + 1
+        );
+    }
+
+    #[test]
+    fn default_opts_are_split_like_a_shell() {
+        assert_eq!(
+            shell_words(r#"--header='--nth is off' --nth 2 "a \"b\"" c\ d ''"#),
+            vec!["--header=--nth is off", "--nth", "2", r#"a "b""#, "c d", ""]
+        );
+
+        let resets = |opts: &str| {
+            Fzf::builder()
+                .default_opts(DefaultOptsPolicy::Merge)
+                .env("FZF_DEFAULT_OPTS", opts)
+                .env_remove("FZF_DEFAULT_OPTS_FILE")
+                .build()
+                .unwrap()
+                .default_opts_resets()
+                .len()
+                - DEFAULT_OPTS_RESETS.len()
+        };
+        assert_eq!(resets("--header='--nth is off' --nth-x"), 0);
+        assert_eq!(resets("--prompt=\"-d \" --with-nth 2"), 1);
+    }
+
+    #[test]
+    fn environment_is_applied_to_the_command() {
+        let fzf = Fzf::builder()
+            .default_opts(DefaultOptsPolicy::Ignore)
+            .env("FZF_DEFAULT_OPTS", "--height=40%")
+            .env_remove("FZF_API_KEY")
+            .current_dir("/tmp")
+            .build()
+            .unwrap();
+        let command = fzf.command(PathBuf::from("fzf"), vec![]);
+        let envs: Vec<_> = command.get_envs().collect();
+
+        assert!(envs.contains(&("FZF_DEFAULT_OPTS".as_ref(), Some("--height=40%".as_ref()))));
+        assert!(envs.contains(&("FZF_DEFAULT_COMMAND".as_ref(), None)));
+        assert!(envs.contains(&("FZF_API_KEY".as_ref(), None)));
+        assert_eq!(command.get_current_dir(), Some(Path::new("/tmp")));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn needed_bindings_are_merged_into_configured_ones() {
+        let fzf = Fzf::builder()
+            .executable("/bin/sh")
+            .select_1(true)
+            .live_search(|_| vec![])
+            .bind(Key::Start, [Action::Last])
+            .bind(Key::Change, [Action::First])
+            .build()
+            .unwrap();
+        let launch = fzf.launch(false).unwrap();
+        let args: Vec<_> = launch
+            .command
+            .get_args()
+            .map(|x| x.to_string_lossy())
+            .collect();
+        let binds = |key: &str| args.iter().filter(|x| x.starts_with(key)).count();
+
+        assert_eq!((binds("--bind=start:"), binds("--bind=change:")), (1, 1));
+        let start = args
+            .iter()
+            .find(|x| x.starts_with("--bind=start:"))
+            .unwrap();
+        assert!(start.starts_with("--bind=start:execute-silent(touch "));
+        assert!(start.contains("+reload("));
+        assert!(start.ends_with("+last"));
+        let change = args
+            .iter()
+            .find(|x| x.starts_with("--bind=change:"))
+            .unwrap();
+        assert!(change.starts_with("--bind=change:reload(") && change.ends_with("+first"));
+
+        let fzf = Fzf::builder()
+            .accept_query(true)
+            .bind(Key::Enter, [Action::Accept])
+            .build();
+        assert!(matches!(fzf, Err(FzfError::InvalidBind(_))));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn live_search_results_are_lines() {
+        let fzf = Fzf::builder()
+            .executable("/bin/sh")
+            .live_search(|query| match query {
+                "" => vec![],
+                _ => vec![query.to_string(), query.to_uppercase()],
+            })
+            .build()
+            .unwrap();
+        let search = fzf.live_search.as_ref().unwrap();
+
+        assert_eq!(search.call(""), "");
+        assert_eq!(search.call("red"), "red\nRED\n");
+        assert!(matches!(fzf.launch(true), Err(FzfError::Builder(_))));
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        let bind = |key| Fzf::builder().bind(key, [Action::Accept]).build();
+        let expect = |key| Fzf::builder().expect([key]).build();
+        let invalid = |fzf: Result<Fzf, FzfError>| matches!(fzf, Err(FzfError::InvalidKey(_)));
+
+        assert!(invalid(bind(Key::F(0))));
+        assert!(invalid(bind(Key::F(13))));
+        assert!(invalid(bind(Key::Ctrl('1'))));
+        assert!(invalid(bind(Key::Ctrl('A'))));
+        assert!(invalid(bind(Key::CtrlAlt(' '))));
+        assert!(invalid(bind(Key::Alt('\n'))));
+        assert!(invalid(bind(Key::Char('\t'))));
+        assert!(invalid(expect(Key::Start)));
+        assert!(invalid(expect(Key::F(13))));
+        assert!(invalid(
+            Fzf::builder().live_search_toggle(Key::Ctrl('?')).build()
+        ));
+
+        assert!(invalid(bind(Key::Char(':'))));
+        assert!(invalid(bind(Key::Alt('+'))));
+        assert!(invalid(expect(Key::Char(','))));
+        assert!(invalid(expect(Key::Alt(':'))));
+        let unbind = Action::Unbind(vec![Key::Change, Key::Char(',')]);
+        assert!(invalid(Fzf::builder().bind(Key::Start, [unbind]).build()));
+
+        assert!(bind(Key::F(12)).is_ok());
+        assert!(bind(Key::Ctrl('/')).is_ok());
+        assert!(invalid(expect(Key::Char(' '))));
+        assert!(expect(Key::Space).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "listen")]
+    fn listen_is_passed_with_its_api_key() {
+        let fzf = Fzf::builder()
+            .executable("/bin/sh")
+            .listen(Listen::Port(6266))
+            .listen_api_key("secret")
+            .build()
+            .unwrap();
+        let launch = fzf.launch(false).unwrap();
+        let args: Vec<_> = launch.command.get_args().collect();
+        let envs: Vec<_> = launch.command.get_envs().collect();
+
+        assert!(args.contains(&"--listen=6266".as_ref()));
+        assert!(envs.contains(&("FZF_API_KEY".as_ref(), Some("secret".as_ref()))));
+        assert!(launch.client.is_some());
+
+        let socket = |path: &str| Fzf::builder().listen(Listen::Socket(path.into())).build();
+        assert!(socket("/tmp/fzf.sock").is_ok());
+        assert!(matches!(socket("/tmp/fzf"), Err(FzfError::Builder(_))));
+        assert!(matches!(
+            socket("/tmp/fzf.sock/"),
+            Err(FzfError::Builder(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn every_emitted_option_is_checked_against_the_version_table() {
+        // Options, and values of options, that `fzf` supported before anything in the table
+        let older = [
+            "--no-multi",
+            "--no-print-query",
+            "--no-expect",
+            "--no-select-1",
+            "--no-exit-0",
+            "--no-read0",
+            "--no-print0",
+            "--no-preview",
+            "--no-tac",
+            "--delimiter",
+            "--nth",
+            "--with-nth",
+            "--literal",
+            "--tac",
+            "--query",
+            "--select-1",
+            "--exit-0",
+            "--multi",
+            "--print-query",
+            "--expect",
+            "--no-mouse",
+            "--cycle",
+            "--no-hscroll",
+            "--filepath-word",
+            "--preview",
+            "--preview-window",
+            "--layout",
+            "--height",
+            "--min-height",
+            "--margin",
+            "--padding",
+            "--border",
+            "--info",
+            "--prompt",
+            "--pointer",
+            "--marker",
+            "--header",
+            "--no-unicode",
+            "--ansi",
+            "--tabstop",
+            "--color",
+            "--no-bold",
+            "--bind",
+        ];
+        let mut colors = ColorScheme::builder();
+        for element in crate::color::ColorElement::ALL {
+            colors.element(element, crate::AnsiColor::Red);
+        }
+        let window = PreviewWindow::builder()
+            .border(Border::Double)
+            .build()
+            .unwrap();
+        let mut builder = Fzf::builder();
+        builder
+            .executable("/bin/sh")
+            .default_opts(DefaultOptsPolicy::Merge)
+            .env(
+                "FZF_DEFAULT_OPTS",
+                "--delimiter=: --nth=1 --with-nth=1 --accept-nth=1",
+            )
+            .scheme(Scheme::Path)
+            .literal(true)
+            .track(true)
+            .tac(true)
+            .delimiter(":")
+            .nth([1])
+            .with_nth([1])
+            .disabled(true)
+            .multi(Multi::Unlimited)
+            .query("red")
+            .select_1(true)
+            .exit_0(true)
+            .accept_query(true)
+            .accept_nth([1])
+            .expect([Key::Ctrl('x')])
+            .no_mouse(true)
+            .cycle(true)
+            .keep_right(true)
+            .no_hscroll(true)
+            .filepath_word(true)
+            .preview("cat {}")
+            .preview_fn(|x| x.to_string())
+            .preview_window(window)
+            .preview_label("Preview")
+            .preview_label_pos(LabelAlign::Center)
+            .live_search(|_| vec![])
+            .live_search_toggle(Key::Ctrl('t'))
+            .layout(Layout::Reverse)
+            .height(Height::Adaptive(Size::Percent(40)))
+            .min_height(10u16)
+            .margin(Size::Lines(1))
+            .padding(Size::Lines(1))
+            .border(Border::ThinBlock)
+            .border_label("Colours")
+            .border_label_pos(LabelAlign::Center)
+            .style(Style::Full)
+            .input_border(Border::Line)
+            .input_label("Input")
+            .input_label_pos(LabelAlign::Center)
+            .list_border(Border::Line)
+            .list_label("List")
+            .list_label_pos(LabelAlign::Center)
+            .header_border(Border::Line)
+            .header_label("Header")
+            .header_label_pos(LabelAlign::Center)
+            .info(Info::InlineRight)
+            .separator("-")
+            .no_separator(true)
+            .scrollbar("|")
+            .no_scrollbar(true)
+            .scroll_off(2u16)
+            .marker("*")
+            .ellipsis("…")
+            .header("Pick one")
+            .header_first(true)
+            .no_unicode(true)
+            .ansi(true)
+            .color(colors.build().unwrap())
+            .no_bold(true);
+        #[cfg(feature = "listen")]
+        builder.listen(Listen::Socket(PathBuf::from("/tmp/fzf.sock")));
+        let launch = builder.build().unwrap().launch(false).unwrap();
+
+        for arg in launch.command.get_args() {
+            let arg = arg.to_string_lossy();
+            let option = arg.split('=').next().unwrap();
+            assert!(
+                older.contains(&option) || !version::requirements(&arg).is_empty(),
+                "{} has no entry in the version table",
+                arg
+            );
+        }
+        let older_values = [
+            "fg",
+            "bg",
+            "hl",
+            "fg+",
+            "bg+",
+            "hl+",
+            "preview-fg",
+            "preview-bg",
+            "gutter",
+            "query",
+            "disabled",
+            "info",
+            "prompt",
+            "pointer",
+            "marker",
+            "spinner",
+            "header",
+            "border",
+            "change:",
+            "backward-eof:",
+        ];
+        for element in crate::color::ColorElement::ALL {
+            let arg = format!("--color=dark,{}:red", element);
+            assert!(
+                older_values.contains(&element.to_string().as_str())
+                    || !version::requirements(&arg).is_empty(),
+                "{} has no entry in the version table",
+                arg
+            );
+        }
+        let events = [
+            Key::Start,
+            Key::Load,
+            Key::Resize,
+            Key::Change,
+            Key::Focus,
+            Key::Result,
+            Key::Zero,
+            Key::One,
+            Key::Multi,
+            Key::BackwardEof,
+            Key::Jump,
+            Key::JumpCancel,
+            Key::ClickHeader,
+        ];
+        for key in events {
+            let arg = format!("--bind={}:accept", key);
+            assert!(
+                older_values.contains(&format!("{}:", key).as_str())
+                    || !version::requirements(&arg).is_empty(),
+                "{} has no entry in the version table",
+                arg
+            );
+        }
+    }
+
+    #[test]
+    fn quoted_paths_survive_the_shell() {
+        assert_eq!(shell_quote("/tmp/fzf"), "'/tmp/fzf'");
+        assert_eq!(shell_quote("/tmp/it's"), "'/tmp/it'\\''s'");
+    }
 }
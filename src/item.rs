@@ -0,0 +1,65 @@
+//! This module contains the [`FzfItem`] trait, which lets any rust value be selected with `fzf`
+
+use std::fmt;
+
+/// Trait for values that can be displayed as an item in `fzf`
+///
+/// This trait is implemented for every type that implements [`fmt::Display`], and can be
+/// implemented manually for types that don't. To show a type that implements [`fmt::Display`]
+/// differently in `fzf`, pass the text to show with [`crate::run_with_selection_by`] instead
+pub trait FzfItem {
+    /// The text to display for the item in `fzf`
+    fn fzf_text(&self) -> String;
+}
+
+impl<T: fmt::Display> FzfItem for T {
+    fn fzf_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Creates the line passed to `fzf` for an item, with its index in a hidden leading field
+///
+/// Newlines in the item's text are replaced with spaces, as `fzf` would otherwise treat them as
+/// separate items
+pub(crate) fn indexed_line(index: usize, text: &str) -> String {
+    format!("{}\t{}", index, text.replace(['\n', '\r'], " "))
+}
+
+/// Gets the index out of a line created with `indexed_line`
+pub(crate) fn parse_index(line: &str) -> Option<usize> {
+    line.split('\t').next()?.parse().ok()
+}
+
+/// Takes the items at the given indexes out of `items`, in the order the indexes are given
+pub(crate) fn take_items<T>(items: Vec<T>, indexes: impl IntoIterator<Item = usize>) -> Vec<T> {
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    indexes
+        .into_iter()
+        .filter_map(|index| items.get_mut(index)?.take())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_survive_the_round_trip() {
+        let items = vec!["red", "red", "multi\nline"];
+
+        let lines: Vec<String> = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| indexed_line(i, item))
+            .collect();
+
+        assert_eq!(lines[2], "2\tmulti line");
+
+        let indexes = lines.iter().rev().filter_map(|x| parse_index(x));
+        assert_eq!(
+            take_items(items, indexes),
+            vec!["multi\nline", "red", "red"]
+        );
+    }
+}
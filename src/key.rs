@@ -0,0 +1,240 @@
+//! This module contains the [`Key`] enum, which represents the keys and events `fzf` can respond
+//! to
+
+use std::fmt;
+
+use crate::error::FzfError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent a key, key chord, or event that `fzf` can respond to
+pub enum Key {
+    /// ctrl and a character, e.g. `Key::Ctrl('v')` is `ctrl-v`
+    ///
+    /// The character is a lowercase letter, space, or one of `\`, `]`, `^` and `/`
+    Ctrl(char),
+    /// alt and a character, e.g. `Key::Alt('d')` is `alt-d`
+    ///
+    /// The character can't be a space or a control character, or one of `,`, `:` and `+`, which
+    /// separate keys and actions in the arguments `fzf` takes. Use `Key::AltSpace` for alt-space
+    Alt(char),
+    /// ctrl, alt and a lowercase letter, e.g. `Key::CtrlAlt('x')` is `ctrl-alt-x`
+    CtrlAlt(char),
+    /// A function key from 1 to 12, e.g. `Key::F(1)` is `f1`
+    F(u8),
+    /// A single character on its own, with the same restrictions as `Alt`. Use `Key::Space` for
+    /// the space key
+    Char(char),
+    Enter,
+    Space,
+    Tab,
+    /// shift-tab
+    BTab,
+    Backspace,
+    Esc,
+    Delete,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    Insert,
+    PageUp,
+    PageDown,
+    ShiftUp,
+    ShiftDown,
+    ShiftLeft,
+    ShiftRight,
+    ShiftDelete,
+    AltEnter,
+    AltSpace,
+    AltBackspace,
+    AltUp,
+    AltDown,
+    AltLeft,
+    AltRight,
+    LeftClick,
+    RightClick,
+    DoubleClick,
+    ScrollUp,
+    ScrollDown,
+    /// Event triggered once when `fzf` starts
+    Start,
+    /// Event triggered when `fzf` has finished reading the input
+    Load,
+    /// Event triggered when the terminal is resized
+    Resize,
+    /// Event triggered when the query changes
+    Change,
+    /// Event triggered when the focused item changes
+    Focus,
+    /// Event triggered when the result list is updated
+    Result,
+    /// Event triggered when no item matches the query
+    Zero,
+    /// Event triggered when exactly one item matches the query
+    One,
+    /// Event triggered when the multi-selection changes
+    Multi,
+    /// Event triggered when backspace is pressed on an empty query
+    BackwardEof,
+    /// Event triggered when an item is selected with the jump action
+    Jump,
+    /// Event triggered when the jump action is cancelled
+    JumpCancel,
+    /// Event triggered when the header is clicked
+    ClickHeader,
+}
+
+impl Key {
+    /// Returns `true` if the key is an event `fzf` triggers, rather than a key the user presses
+    pub fn is_event(&self) -> bool {
+        matches!(
+            self,
+            Self::Start
+                | Self::Load
+                | Self::Resize
+                | Self::Change
+                | Self::Focus
+                | Self::Result
+                | Self::Zero
+                | Self::One
+                | Self::Multi
+                | Self::BackwardEof
+                | Self::Jump
+                | Self::JumpCancel
+                | Self::ClickHeader
+        )
+    }
+
+    /// Checks that `fzf` has a name for the key
+    pub(crate) fn validate(&self) -> Result<(), FzfError> {
+        // Only one form of each key is allowed, so that keys compare equal to themselves
+        let named = match self {
+            Self::Char(' ') => Some(Self::Space),
+            Self::Alt(' ') => Some(Self::AltSpace),
+            _ => None,
+        };
+        if let Some(named) = named {
+            return Err(FzfError::InvalidKey(format!(
+                "{:?} must be given as {:?}",
+                self, named
+            )));
+        }
+
+        let valid = match *self {
+            Self::Ctrl(c) => c.is_ascii_lowercase() || matches!(c, ' ' | '\\' | ']' | '^' | '/'),
+            Self::CtrlAlt(c) => c.is_ascii_lowercase(),
+            // These would be read as the end of the key in `--expect` and `--bind`
+            Self::Alt(',' | ':' | '+') | Self::Char(',' | ':' | '+') => false,
+            Self::Alt(c) | Self::Char(c) => !(c.is_control() || c.is_whitespace()),
+            Self::F(n) => (1..=12).contains(&n),
+            _ => true,
+        };
+        match valid {
+            true => Ok(()),
+            false => Err(FzfError::InvalidKey(format!(
+                "{:?} has no name in fzf",
+                self
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            // `fzf` names the space key, as a space would be taken as the end of the key
+            Self::Ctrl(' ') => "ctrl-space",
+            Self::Ctrl(c) => return write!(f, "ctrl-{}", c),
+            Self::Alt(c) => return write!(f, "alt-{}", c),
+            Self::CtrlAlt(c) => return write!(f, "ctrl-alt-{}", c),
+            Self::F(n) => return write!(f, "f{}", n),
+            Self::Char(c) => return write!(f, "{}", c),
+            Self::Enter => "enter",
+            Self::Space => "space",
+            Self::Tab => "tab",
+            Self::BTab => "btab",
+            Self::Backspace => "bspace",
+            Self::Esc => "esc",
+            Self::Delete => "del",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Home => "home",
+            Self::End => "end",
+            Self::Insert => "insert",
+            Self::PageUp => "pgup",
+            Self::PageDown => "pgdn",
+            Self::ShiftUp => "shift-up",
+            Self::ShiftDown => "shift-down",
+            Self::ShiftLeft => "shift-left",
+            Self::ShiftRight => "shift-right",
+            Self::ShiftDelete => "shift-delete",
+            Self::AltEnter => "alt-enter",
+            Self::AltSpace => "alt-space",
+            Self::AltBackspace => "alt-bspace",
+            Self::AltUp => "alt-up",
+            Self::AltDown => "alt-down",
+            Self::AltLeft => "alt-left",
+            Self::AltRight => "alt-right",
+            Self::LeftClick => "left-click",
+            Self::RightClick => "right-click",
+            Self::DoubleClick => "double-click",
+            Self::ScrollUp => "scroll-up",
+            Self::ScrollDown => "scroll-down",
+            Self::Start => "start",
+            Self::Load => "load",
+            Self::Resize => "resize",
+            Self::Change => "change",
+            Self::Focus => "focus",
+            Self::Result => "result",
+            Self::Zero => "zero",
+            Self::One => "one",
+            Self::Multi => "multi",
+            Self::BackwardEof => "backward-eof",
+            Self::Jump => "jump",
+            Self::JumpCancel => "jump-cancel",
+            Self::ClickHeader => "click-header",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+/// Creates the comma separated list of keys `fzf` expects for options like `--expect`
+pub(crate) fn key_list(keys: &[Key]) -> String {
+    keys.iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spaces_are_named() {
+        assert_eq!(Key::Ctrl(' ').to_string(), "ctrl-space");
+        assert_eq!(key_list(&[Key::Ctrl('\\'), Key::Space]), "ctrl-\\,space");
+    }
+
+    #[test]
+    fn spaces_only_have_one_form() {
+        assert!(Key::Char(' ').validate().is_err());
+        assert!(Key::Alt(' ').validate().is_err());
+        assert!(Key::Space.validate().is_ok());
+        assert!(Key::AltSpace.validate().is_ok());
+    }
+
+    #[test]
+    fn separators_are_rejected() {
+        for c in [',', ':', '+'] {
+            assert!(Key::Char(c).validate().is_err(), "{}", c);
+            assert!(Key::Alt(c).validate().is_err(), "{}", c);
+        }
+        assert!(Key::Char('.').validate().is_ok());
+        assert!(Key::Alt('/').validate().is_ok());
+    }
+}
@@ -7,79 +7,102 @@
 //!
 //! **NOTE** this does mean that the end user must have `fzf` installed on their system.
 //!
+//! #### Finding fzf
+//!
+//! By default `fzf` is found using the `FZF_WRAPPED_BIN` environment variable, falling back to
+//! searching `PATH`. The `executable` option on the [`FzfBuilder`] runs a specific executable
+//! instead, such as a vendored copy, `fzf-tmux`, or a wrapper script. The [`find_fzf`] function
+//! can be used to check `fzf` is installed up front, otherwise running `fzf` returns
+//! `FzfError::NotInstalled`.
+//!
+//! #### Default options
+//!
+//! `fzf` reads the user's defaults from the `FZF_DEFAULT_OPTS` environment variable, which can
+//! change what it prints, for example when they've set `--multi`. The `default_opts` option takes
+//! a [`DefaultOptsPolicy`] to either inherit them, ignore them, or merge them with the configured
+//! options so the user keeps their look and feel. The `env`, `env_remove` and `current_dir` options
+//! control the rest of the environment `fzf` is run in.
+//!
 //! #### fzf version
-//! 
-//! This crate was developed with `fzf` v0.40.0 in mind, however there should be no reason why it
-//! shouldn't work above it, and most of the features should work below it. If your program relies
-//! on v0.40.0 features, it might be a good idea to check the version of `fzf` your program has
-//! access to
+//!
+//! This crate was developed with `fzf` v0.40.0 in mind, however most options work with older
+//! versions too. The [`fzf_version`] function gets the version of `fzf` that is installed, and the
+//! `version_policy` option on the [`FzfBuilder`] checks each option against it before running
+//! `fzf`. [`VersionPolicy::Error`] fails with an error naming the first unsupported option, while
+//! [`VersionPolicy::Warn`] drops unsupported options and lists them in the session's `warnings()`.
 //!
 //! ## Example
 //!
 //! Say we're wanting to get the user to select their favourite colour using the power of fuzzy
 //! finding. First we'd create a list of colours, which will be a collection we'll pass to `fzf`
 //!
-//! ```rust
+//! ```no_run
 //! let colours = vec!["red", "orange", "yellow", "green", "blue", "indigo", "violet"];
 //! ```
 //!
 //! The next step is to construct an instance of [`Fzf`] and start it:
 //!
-//! ```rust
-//! let mut fzf = Fzf::default();
+//! ```no_run
+//! # use fzf_wrapped::Fzf;
+//! let fzf = Fzf::default();
 //!
-//! fzf.run().expect("Failed to start fzf");
+//! let mut session = fzf.run().expect("Failed to start fzf");
 //! ```
 //!
 //! The code above fetches the default [`Fzf`] configuration, which runs `fzf` with no arguments,
-//! and then calls the `run()` method. This displays `fzf` to the user.
+//! and then calls the `run()` method. This displays `fzf` to the user, and returns an
+//! [`FzfSession`] representing the running instance of `fzf`. The configuration itself isn't
+//! consumed, so it can be run again later to show `fzf` as many times as you like.
 //!
 //! At the moment all that will be displayed is a blank screen, as we haven't actually told `fzf`
 //! to display anything. There are two ways to do this, the `add_item()` method, and the
-//! `add_items()` method. They are both nearly identical, with the only difference being that
+//! `add_items()` method on the session. They are both nearly identical, with the only difference being that
 //! `add_items()` takes a [`Vec`] of [`String`]'s as items, and passes them one by one to
 //! `add_item()`.
 //!
-//! ```rust
-//! # let mut fzf = Fzf::default();
-//! # fzf.run().expect("Failed to start fzf");
-//! fzf.add_items(colours).expect("Failed to add items");
+//! ```no_run
+//! # use fzf_wrapped::Fzf;
+//! # let colours = vec!["red", "orange", "yellow", "green", "blue", "indigo", "violet"];
+//! # let mut session = Fzf::default().run().expect("Failed to start fzf");
+//! session.add_items(colours).expect("Failed to add items");
 //! ```
 //!
 //! The only thing left to do is to get what the user selected! This will be returned as an
-//! [`Option`] containing `None` if the user exited `fzf`, or `Some(String)` with the string being
-//! the item they selected. To get the output we simply call the `output()` method, which will
-//! blocks execution until the user selects an item with `fzf`
-//!
-//! ```rust
-//! # let mut fzf = Fzf::default();
-//! # fzf.run().expect("Failed to start fzf");
-//! # fzf.add_items(colours).expect("Failed to add items");
-//! let users_selection = fzf.output().expect("Failed to get the user's output");
+//! [`FzfOutcome`], which is `FzfOutcome::Selected(String)` with the string being the item they
+//! selected, or one of the other variants if the user quit `fzf`, nothing matched, or `fzf` failed.
+//! To get the output we simply call the `output()` method, which will blocks execution until the
+//! user selects an item with `fzf`
+//!
+//! ```no_run
+//! # use fzf_wrapped::Fzf;
+//! # let colours = vec!["red", "orange", "yellow", "green", "blue", "indigo", "violet"];
+//! # let mut session = Fzf::default().run().expect("Failed to start fzf");
+//! # session.add_items(colours).expect("Failed to add items");
+//! let users_selection = session.output().expect("Failed to get the user's output");
 //! ```
 //!
-//! The code in it's entirety looks like the following. 
-//! 
-//! ```rust
+//! The code in it's entirety looks like the following.
+//!
+//! ```no_run
 //! use fzf_wrapped::Fzf;
 //!
 //! fn main() {
 //!     let colours = vec!["red", "orange", "yellow", "green", "blue", "indigo", "violet"];
 //!     
-//!     let mut fzf = Fzf::default();
-//!     fzf.run().expect("Failed to start fzf");
+//!     let fzf = Fzf::default();
+//!     let mut session = fzf.run().expect("Failed to start fzf");
 //!
-//!     fzf.add_items(colours).expect("Failed to add items");
+//!     session.add_items(colours).expect("Failed to add items");
 //!
-//!     let users_selection = fzf.output().expect("Failed to get the user's output");
+//!     let users_selection = session.output().expect("Failed to get the user's output");
 //! }
 //! ```
 //!
 //! This operation of using `fzf` to select from a predetermined [`Vec`] of items is so common that
-//! a helper function exists to streamline the work involved. Using it, the code looks like the 
+//! a helper function exists to streamline the work involved. Using it, the code looks like the
 //! following:
 //!
-//! ```rust
+//! ```no_run
 //! use fzf_wrapped::Fzf;
 //! use fzf_wrapped::run_with_output;
 //!
@@ -97,12 +120,14 @@
 //!
 //! Thankfully `fzf` provides many different ways to customize it, many of which have been
 //! implemented in this library. So far we have been calling the [`Fzf`] structs default
-//! implementation, however we can build more complex instances using [`FzfBuilder`]. 
+//! implementation, however we can build more complex instances using [`FzfBuilder`].
 //!
 //! We can use two different ways to get an [`FzfBuilder`], either through it's own `new()` method,
 //! or the `builder()` method on [`Fzf`]. Let's switch out the default call for a builder call.
 //!
-//! ```rust
+//! ```no_run
+//! # use fzf_wrapped::{Fzf, run_with_output};
+//! # let colours = vec!["red", "orange", "yellow", "green", "blue", "indigo", "violet"];
 //! let fzf = Fzf::builder().build().unwrap();
 //!
 //! let users_selection = run_with_output(fzf, colours).expect("Something went wrong!");
@@ -113,8 +138,8 @@
 //! If we run the program now, we'll notice... nothing has changed. This is because the `default()`
 //! method calls the exact line of code we just replaced it with.
 //!
-//! Let's make things interesting! First we should probably give the finder a label. Without a 
-//! border, the label won't show so lets give it a border too! 
+//! Let's make things interesting! First we should probably give the finder a label. Without a
+//! border, the label won't show so lets give it a border too!
 //!
 //! ### Adding a border and border label
 //!
@@ -126,7 +151,7 @@
 //!
 //! Adding a rounded border makes our code look like this:
 //!
-//! ```rust
+//! ```no_run
 //! use fzf_wrapped::Fzf;
 //! use fzf_wrapped::Border;
 //! use fzf_wrapped::run_with_output;
@@ -145,7 +170,7 @@
 //!
 //! And adding a label is even more simple
 //!
-//! ```rust
+//! ```no_run
 //! use fzf_wrapped::Fzf;
 //! use fzf_wrapped::Border;
 //! use fzf_wrapped::run_with_output;
@@ -166,6 +191,25 @@
 //! Running this should display an `fzf` finder with a rounded border, and a centered label
 //! containing "Favourite Colour".
 //!
+//! The label doesn't have to be centered. The `border_label_pos()` method takes a
+//! [`LabelPosition`], which places the label a number of columns from the left or right, on either
+//! the top or bottom border line. Newer versions of `fzf` can also draw borders around the input,
+//! list and header sections, each with their own label, or use one of the [`Style`] presets.
+//!
+//! ```
+//! use fzf_wrapped::{Border, Fzf, LabelAlign, LabelPosition, Style};
+//!
+//! let fzf = Fzf::builder()
+//!     .style(Style::Minimal)
+//!     .border(Border::Double)
+//!     .border_label("Favourite Colour")
+//!     .border_label_pos(LabelPosition::bottom(LabelAlign::Right(2)))
+//!     .list_border(Border::Rounded)
+//!     .list_label("Colours")
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
 //! ### Changing the layout of `fzf`
 //!
 //! Well, now that we've got a border, we may as well change up the layout. This is almost
@@ -173,7 +217,7 @@
 //!
 //! All we need to add to our builder is the `layout()` method with our chosen [`Layout`] variant
 //!
-//! ```rust
+//! ```no_run
 //! use fzf_wrapped::Fzf;
 //! use fzf_wrapped::{Border, Layout};
 //! use fzf_wrapped::run_with_output;
@@ -198,7 +242,7 @@
 //! use the black and white theme. Similar to borders and the layout, this is selected using an
 //! enum. Adding it to our builder results in the following code:
 //!
-//! ```rust
+//! ```no_run
 //! use fzf_wrapped::Fzf;
 //! use fzf_wrapped::{Border, Color, Layout};
 //! use fzf_wrapped::run_with_output;
@@ -223,11 +267,11 @@
 //! ### Adding a header
 //!
 //! Our user might still be confused about what they're picking, so to add some more context, `fzf`
-//! lets us set a header. To do this all we do is call the `header()` method on our [`FzfBuilder`] 
+//! lets us set a header. To do this all we do is call the `header()` method on our [`FzfBuilder`]
 //! struct, and pass it anything with the `Into<String>` trait. We also want the header to appear
 //! above our search field, so we'll call the `header_first()` method with `true`.
 //!
-//! ```rust
+//! ```no_run
 //! use fzf_wrapped::Fzf;
 //! use fzf_wrapped::{Border, Color, Layout};
 //! use fzf_wrapped::run_with_output;
@@ -249,19 +293,40 @@
 //! }
 //! ```
 //!
+//! ### Changing the size of `fzf`
+//!
+//! Say we wanted to make our colour picker program not take up the full screen, say only 10 lines
+//! of it. The `height()` method takes a [`Height`], which is either a fixed [`Size`] in lines or as
+//! a percentage, or an adaptive one that shrinks to fit when there are only a few items. The
+//! `margin()` and `padding()` methods add space outside and inside the border, and take either a
+//! single [`Size`] or a tuple of two to four of them, in the same order as CSS.
+//!
+//! ```
+//! use fzf_wrapped::{Fzf, Height, Size};
+//!
+//! let fzf = Fzf::builder()
+//!     .height(Height::Adaptive(Size::Lines(10)))
+//!     .margin((1, Size::Percent(5)))
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
+//! Options such as `info()`, `marker()`, `ellipsis()`, `scroll_off()`, `scrollbar()`, `separator()`
+//! and `no_unicode()` change the rest of the interface.
+//!
 //! ### Using an argument not supported by `fzf_wrapped`
 //!
 //! If by chance the argument you want to run with `fzf` is not a method included on the [`Fzf`]
 //! struct, do not worry! The `custom_args()` command will let you pass any argument you want! For
-//! example, say we wanted to make our colour picker program not take up the full screen, say only
-//! 10% of it. `fzf` has the `--height=` flag, however the [`Fzf`] struct doesn't support it! To
-//! add it all we'll need to do is to call the `custom_args()` command on our builder, and the
+//! example, say we wanted our colours to stay in rainbow order, rather than being sorted by how
+//! well they match. `fzf` has the `--no-sort` flag, however the [`Fzf`] struct doesn't support it!
+//! To add it all we'll need to do is to call the `custom_args()` command on our builder, and the
 //! arguments we pass into it will be run with the `run()` method.
 //!
 //! Implementing it would look like:
 //!
-//! ```rust
-//! use fzf_wrapped::Fzf;
+//! ```no_run
+//! use fzf_wrapped::{Fzf, FzfOutcome};
 //! use fzf_wrapped::{Border, Color, Layout};
 //! use fzf_wrapped::run_with_output;
 //!
@@ -275,13 +340,13 @@
 //!         .color(Color::Bw)
 //!         .header("Pick your favourite colour")
 //!         .header_first(true)
-//!         .custom_args(vec!["--height=10".to_string()])
+//!         .custom_args(vec!["--no-sort".to_string()])
 //!         .build()
 //!         .unwrap();
 //!     
 //!     let users_selection = run_with_output(fzf, colours);
 //!
-//!     if let Some(colour) = users_selection {
+//!     if let Ok(FzfOutcome::Selected(colour)) = users_selection {
 //!         println!("{} is an awesome colour!", colour);
 //!     }
 //! }
@@ -292,8 +357,8 @@
 //! All we need to do is print some kind of nice message, and while we're at it, we may as well use
 //! some proper error handling.
 //!
-//! ```rust
-//! use fzf_wrapped::Fzf;
+//! ```no_run
+//! use fzf_wrapped::{Fzf, FzfOutcome};
 //! use fzf_wrapped::{Border, Color, Layout};
 //! use fzf_wrapped::run_with_output;
 //!
@@ -307,46 +372,793 @@
 //!         .color(Color::Bw)
 //!         .header("Pick your favourite colour")
 //!         .header_first(true)
-//!         .custom_args(vec!["--height=10".to_string()])
+//!         .custom_args(vec!["--no-sort".to_string()])
 //!         .build()
 //!         .unwrap();
 //!     
 //!     let users_selection = run_with_output(fzf, colours);
 //!
-//!     if let Some(colour) = users_selection {
-//!         println!("{} is an awesome colour!", colour);
+//!     match users_selection {
+//!         Ok(FzfOutcome::Selected(colour)) => println!("{} is an awesome colour!", colour),
+//!         Ok(FzfOutcome::Aborted) => println!("Maybe next time!"),
+//!         Ok(_) => println!("That's not a colour!"),
+//!         Err(e) => eprintln!("Failed to run fzf: {}", e),
+//!     }
+//! }
+//! ```
+//!
+//! ## Selecting multiple items
+//!
+//! Sometimes one item isn't enough, `fzf` lets the user pick several items with tab/shift-tab when
+//! it is started with the `--multi` flag. This is enabled with the `multi()` method on the
+//! [`FzfBuilder`], passing it a variant of the [`Multi`] enum. The selections can then be fetched
+//! with the `output_multi()` method, or with the `run_with_output_multi()` helper function, both
+//! of which return each selected item as its own [`String`].
+//!
+//! ```no_run
+//! use fzf_wrapped::Fzf;
+//! use fzf_wrapped::{Multi, MultiOrder};
+//! use fzf_wrapped::run_with_output_multi;
+//!
+//! fn main() {
+//!     let colours = vec!["red", "orange", "yellow", "green", "blue", "indigo", "violet"];
+//!
+//!     let fzf = Fzf::builder()
+//!         .multi(Multi::Limit(3))
+//!         .multi_order(MultiOrder::Input)
+//!         .build()
+//!         .unwrap();
+//!
+//!     let users_selections = run_with_output_multi(fzf, colours);
+//! }
+//! ```
+//!
+//! By default the selections are returned in the order the user picked them, passing
+//! [`MultiOrder::Input`] to the `multi_order()` method returns them in the order they were given
+//! to `fzf` instead.
+//!
+//! ## Selecting rust values
+//!
+//! `fzf` only deals in text, which means `run_with_output` can only tell you the text of the item
+//! that was selected. If you want the original value back, the `run_with_selection()` and
+//! `run_with_selection_multi()` functions take any type implementing the [`FzfItem`] trait, and
+//! return the value the user selected. [`FzfItem`] is implemented for every type implementing
+//! [`std::fmt::Display`], and can be implemented manually for types that don't.
+//!
+//! ```no_run
+//! use fzf_wrapped::{Fzf, FzfOutcome};
+//! use fzf_wrapped::FzfItem;
+//! use fzf_wrapped::run_with_selection;
+//!
+//! struct Colour {
+//!     name: &'static str,
+//!     hex: u32,
+//! }
+//!
+//! impl FzfItem for Colour {
+//!     fn fzf_text(&self) -> String {
+//!         self.name.to_string()
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let colours = vec![
+//!         Colour { name: "red", hex: 0xff0000 },
+//!         Colour { name: "green", hex: 0x00ff00 },
+//!         Colour { name: "blue", hex: 0x0000ff },
+//!     ];
+//!
+//!     if let Ok(FzfOutcome::Selected(colour)) = run_with_selection(Fzf::default(), colours) {
+//!         println!("{} is #{:06x}", colour.name, colour.hex);
+//!     }
+//! }
+//! ```
+//!
+//! Types that already implement [`std::fmt::Display`] can't implement [`FzfItem`] themselves, so to
+//! show them differently in `fzf`, pass the text to show for each item to
+//! `run_with_selection_by()` or `run_with_selection_multi_by()` instead.
+//!
+//! ```no_run
+//! use fzf_wrapped::{Fzf, FzfOutcome};
+//! use fzf_wrapped::run_with_selection_by;
+//!
+//! fn main() {
+//!     let ports: Vec<u16> = vec![22, 80, 443];
+//!
+//!     let outcome = run_with_selection_by(Fzf::default(), ports, |port| format!("port {}", port));
+//!     if let Ok(FzfOutcome::Selected(port)) = outcome {
+//!         println!("Connecting to {}", port);
+//!     }
+//! }
+//! ```
+//!
+//! Under the hood each item is passed to `fzf` with its index in a hidden field, which is read
+//! back out of `fzf`'s output to find the selected value.
+//!
+//! ## Accepting with different keys
+//!
+//! Pickers often want different keys to do different things with the selection, say enter to open
+//! a file, ctrl-v to open it in a split, and ctrl-d to delete it. The `expect()` method on the
+//! [`FzfBuilder`] takes the keys, as variants of the [`Key`] enum, that should accept the
+//! selection. The `output_selection()` method then returns a [`Selection`], which holds which of
+//! those keys was pressed separately from the selected items.
+//!
+//! ```no_run
+//! use fzf_wrapped::{Fzf, FzfOutcome, Key};
+//!
+//! fn main() {
+//!     let fzf = Fzf::builder()
+//!         .expect([Key::Ctrl('v'), Key::Ctrl('d')])
+//!         .build()
+//!         .unwrap();
+//!
+//!     let mut session = fzf.run().expect("Failed to start fzf");
+//!     session.add_items(["README.md", "Cargo.toml"]).expect("Failed to add items");
+//!
+//!     if let Ok(FzfOutcome::Selected(selection)) = session.output_selection() {
+//!         match selection.key {
+//!             Some(Key::Ctrl('v')) => println!("Opening {:?} in a split", selection.items),
+//!             Some(Key::Ctrl('d')) => println!("Deleting {:?}", selection.items),
+//!             _ => println!("Opening {:?}", selection.items),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! ## Accepting the query
+//!
+//! Sometimes the user should be able to enter something that isn't in the list, like picking an
+//! existing tag or creating a new one. Setting the `accept_query` option lets enter accept the query
+//! the user typed when nothing matches it, and the `output_accepted()` method returns an
+//! [`Accepted`] saying whether an item or the query was accepted. If you only need the query
+//! alongside the selection, the `print_query` option fills in the `query` field of [`Selection`].
+//!
+//! ```no_run
+//! use fzf_wrapped::{Accepted, Fzf, FzfOutcome};
+//!
+//! fn main() {
+//!     let fzf = Fzf::builder().accept_query(true).build().unwrap();
+//!
+//!     let mut session = fzf.run().expect("Failed to start fzf");
+//!     session.add_items(["bug", "feature"]).expect("Failed to add items");
+//!
+//!     match session.output_accepted() {
+//!         Ok(FzfOutcome::Selected(Accepted::Item(tag))) => println!("Using tag {}", tag),
+//!         Ok(FzfOutcome::Selected(Accepted::Query(tag))) => println!("Creating tag {}", tag),
+//!         _ => println!("No tag chosen"),
+//!     }
+//! }
+//! ```
+//!
+//! ## Skipping the ui
+//!
+//! Scripts often know the answer before asking. The `query` option fills in the search box, while
+//! the `select_1` and `exit_0` options let `fzf` return straight away when exactly one or no items
+//! match. The [`FzfOutcome`] then tells you it didn't ask the user, with the `AutoSelected` and
+//! `AutoExited` variants. Telling these apart needs a POSIX shell, so on platforms other than unix
+//! they're reported as `Selected` and `NoMatch`.
+//!
+//! ```no_run
+//! use fzf_wrapped::{Fzf, FzfOutcome};
+//!
+//! fn main() {
+//!     let fzf = Fzf::builder()
+//!         .query("feature")
+//!         .select_1(true)
+//!         .exit_0(true)
+//!         .build()
+//!         .unwrap();
+//!
+//!     let mut session = fzf.run().expect("Failed to start fzf");
+//!     session.add_items(["main", "feature/login"]).expect("Failed to add items");
+//!
+//!     match session.output() {
+//!         Ok(FzfOutcome::AutoSelected(branch)) => println!("Only {} matched", branch),
+//!         Ok(FzfOutcome::Selected(branch)) => println!("Checking out {}", branch),
+//!         Ok(FzfOutcome::AutoExited) => println!("No branch matched"),
+//!         _ => println!("No branch chosen"),
+//!     }
+//! }
+//! ```
+//!
+//! ## Filtering without the ui
+//!
+//! `fzf` can also rank items against a query without a terminal, which is handy in CI or batch
+//! commands. The `filter()` method on [`Fzf`] returns the items matching the query, best match
+//! first, using the same `scheme`, `literal` and `tac` options as the interactive ui. The
+//! `filter_items()` method does the same for any rust values implementing [`FzfItem`].
+//!
+//! ```no_run
+//! use fzf_wrapped::Fzf;
+//!
+//! fn main() {
+//!     let fzf = Fzf::builder().build().unwrap();
+//!
+//!     let matches = fzf
+//!         .filter(["src/lib.rs", "src/fzf.rs", "README.md"], "src")
+//!         .expect("Failed to run fzf");
+//!
+//!     println!("Best match: {:?}", matches.first());
+//! }
+//! ```
+//!
+//! ## Binding keys
+//!
+//! The `bind()` method on the [`FzfBuilder`] binds a [`Key`], or an event such as `Key::Change`,
+//! to a chain of [`Action`]s. Payloads like commands are wrapped in delimiters `fzf` won't confuse
+//! with their contents, and bindings that can't be passed to `fzf` are caught when the [`Fzf`] is
+//! built rather than when it runs.
+//!
+//! ```no_run
+//! use fzf_wrapped::{Action, Fzf, Key};
+//!
+//! fn main() {
+//!     let fzf = Fzf::builder()
+//!         .bind(Key::Ctrl('a'), [Action::SelectAll, Action::Accept])
+//!         .bind(Key::Ctrl('o'), [Action::Execute("vim {}".to_string())])
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! ## Previewing items
+//!
+//! The `preview` option runs a command for the focused item, replacing `{}` with the item, and
+//! shows its output next to the list. The `preview_window` option takes a [`PreviewWindow`], built
+//! with its own builder, to set where the preview is shown, how big it is, and how it scrolls,
+//! including a different layout for narrow terminals.
+//!
+//! ```no_run
+//! use fzf_wrapped::{Fzf, PreviewPosition, PreviewWindow, Size};
+//!
+//! fn main() {
+//!     let below = PreviewWindow::builder()
+//!         .position(PreviewPosition::Down)
+//!         .build()
+//!         .unwrap();
+//!
+//!     let fzf = Fzf::builder()
+//!         .preview("cat {}")
+//!         .preview_window(
+//!             PreviewWindow::builder()
+//!                 .size(Size::Percent(60))
+//!                 .wrap(true)
+//!                 .alternative(80, below)
+//!                 .build()
+//!                 .unwrap(),
+//!         )
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! ### Previewing with rust closures
+//!
+//! When the preview comes from data your program already has, the `preview_fn()` method on the
+//! [`FzfBuilder`] takes a closure instead of a command, which is given the text of the focused
+//! item. For rust values, the [`run_with_preview`] function passes the focused value itself to the
+//! closure. `fzf` gets the preview by running your
+//! program again, which connects back to the running session over localhost, so
+//! [`handle_self_invocation`] must be called at the very start of `main`. The command `fzf` runs
+//! is quoted for a POSIX shell, so closure previews and live search are only available on unix.
+//!
+//! ```no_run
+//! use fzf_wrapped::{handle_self_invocation, run_with_preview, Fzf, FzfOutcome};
+//!
+//! struct User {
+//!     name: String,
+//!     email: String,
+//! }
+//!
+//! impl std::fmt::Display for User {
+//!     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+//!         write!(f, "{}", self.name)
+//!     }
+//! }
+//!
+//! fn main() {
+//!     handle_self_invocation();
+//!
+//!     let users = vec![User {
+//!         name: "Ferris".to_string(),
+//!         email: "ferris@rust-lang.org".to_string(),
+//!     }];
+//!
+//!     let outcome = run_with_preview(Fzf::default(), users, |user| user.email.clone());
+//!     if let Ok(FzfOutcome::Selected(users)) = outcome {
+//!         println!("Selected {} users", users.len());
 //!     }
 //! }
 //! ```
 //!
+//! ### Searching as you type
+//!
+//! For pickers that search a codebase or an api, the `live_search()` method on the [`FzfBuilder`]
+//! takes a closure that gets the query and returns the matching items. `fzf`'s own search is
+//! disabled, and the closure is run again whenever the query changes, once the user has stopped
+//! typing for the `live_search_debounce` duration. Results for an old query are thrown away. The
+//! `live_search_toggle` option sets a key that switches over to `fzf` filtering the current results.
+//! Like closure previews, this needs [`handle_self_invocation`] to be called at the start of `main`.
+//!
+//! ```no_run
+//! use fzf_wrapped::{handle_self_invocation, Fzf, Key};
+//!
+//! fn main() {
+//!     handle_self_invocation();
+//!
+//!     let fzf = Fzf::builder()
+//!         .live_search(|query| vec![format!("{}.rs", query), format!("{}.toml", query)])
+//!         .live_search_toggle(Key::Ctrl('f'))
+//!         .build()
+//!         .unwrap();
+//!
+//!     let session = fzf.run().expect("Failed to start fzf");
+//!     println!("{:?}", session.output());
+//! }
+//! ```
+//!
+//! ## Colour schemes
+//!
+//! When the base themes aren't enough, a [`ColorScheme`] draws parts of the ui differently on top of
+//! one of them. Each [`ColorElement`], such as the highlighted matches or the current line, can be
+//! given one of the 16 ANSI colours, a 256-colour index or a `#rrggbb` colour, along with attributes
+//! such as bold, underline and dim. A [`Color`] can still be passed on its own wherever a scheme is
+//! expected.
+//!
+//! ```
+//! use fzf_wrapped::{AnsiColor, Color, ColorAttr, ColorElement, ColorSpec, ColorValue};
+//! use fzf_wrapped::{ColorScheme, Fzf};
+//!
+//! let scheme = ColorScheme::builder()
+//!     .base(Color::Dark)
+//!     .element(ColorElement::Hl, AnsiColor::BrightYellow)
+//!     .element(ColorElement::CurrentBg, ColorValue::Rgb(0x28, 0x2c, 0x34))
+//!     .element(ColorElement::Prompt, ColorSpec::new(39).attr(ColorAttr::Bold))
+//!     .element(ColorElement::Header, ColorAttr::Dim)
+//!     .build()
+//!     .unwrap();
+//!
+//! let fzf = Fzf::builder().color(scheme).build().unwrap();
+//! ```
+//!
+//! ### Theme presets and theme files
+//!
+//! A few [`ThemePreset`]s come ready to use, such as a high contrast theme, Solarized in both
+//! variants, and a monochrome theme that picks out matches with attributes alone. They can be passed
+//! straight to the `color()` method, or used as the start of a [`ColorSchemeBuilder`]. To share one
+//! theme between tools, the `themes` feature adds `ColorScheme::load()`, which reads a small TOML
+//! or JSON theme file. The file can name a preset to start from, a base [`Color`], and the colours
+//! of each part of the ui the way `fzf` writes them.
+//!
+//! ```
+//! use fzf_wrapped::{Fzf, ThemePreset};
+//!
+//! let fzf = Fzf::builder().color(ThemePreset::HighContrast).build().unwrap();
+//! ```
+//!
+//! ```toml
+//! preset = "solarized-dark"
+//!
+//! [colors]
+//! hl = "bright-yellow:bold"
+//! "bg+" = "#282c34"
+//! ```
+//!
+//! ```toml
+//! fzf-wrapped = { version = "0.1", features = ["themes"] }
+//! ```
+//!
+//! ```ignore
+//! use fzf_wrapped::{ColorScheme, Fzf, ThemePreset};
+//!
+//! let theme = ColorScheme::load("team-theme.toml").unwrap_or(ThemePreset::Monochrome.into());
+//! let fzf = Fzf::builder().color(theme).build().unwrap();
+//! ```
+//!
+//! ## Controlling a running `fzf`
+//!
+//! Setting the `listen` option makes `fzf` start an http server, and the `client()` method on the
+//! session returns a `FzfClient` for it. The client can be moved to another thread, where it can
+//! run actions such as `change_query()`, `reload()` and `toggle_all()` while the user is making their
+//! selection, and read the query, matches and selections with `state()`. `fzf` can listen on a port,
+//! or on a unix socket with newer versions, and `listen_api_key` sets a secret every request must
+//! include. The option and client are enabled with the `listen` feature.
+//!
+//! ```toml
+//! fzf-wrapped = { version = "0.1", features = ["listen"] }
+//! ```
+//!
+//! ```ignore
+//! use std::{thread, time::Duration};
+//!
+//! use fzf_wrapped::{Fzf, Listen};
+//!
+//! let fzf = Fzf::builder()
+//!     .listen(Listen::AnyPort)
+//!     .listen_api_key("hunter2")
+//!     .build()
+//!     .unwrap();
+//!
+//! let mut session = fzf.run().expect("Failed to start fzf");
+//! session.add_items(["red", "green", "blue"]).expect("Failed to add items");
+//!
+//! let client = session.client().unwrap();
+//! thread::spawn(move || {
+//!     thread::sleep(Duration::from_secs(5));
+//!     if let Ok(state) = client.state() {
+//!         let _ = client.change_header(format!("{} colours match", state.match_count));
+//!     }
+//! });
+//!
+//! println!("{:?}", session.output());
+//! ```
+//!
+//! ## Working with fields
+//!
+//! Items are often records, such as `id<TAB>name<TAB>path`. The `delimiter` option sets what
+//! separates their fields, which are separated by whitespace by default. Each field option takes a
+//! list of [`FieldRange`]s, which count from `1`, or from the last field when negative: `with_nth`
+//! picks the fields shown, `nth` picks which of the shown fields are searched, and `accept_nth` picks
+//! the fields printed when an item is selected. The `output_fields()` method on the session splits
+//! the selection back into its fields. As only the picked fields are printed, `accept_nth` can't be
+//! combined with `MultiOrder::Input`, which needs the whole item to sort the selections.
+//!
+//! ```no_run
+//! use fzf_wrapped::{FieldRange, Fzf, FzfOutcome};
+//!
+//! let records = ["1\tred\t/tmp/red", "2\tgreen\t/tmp/green"];
+//!
+//! let fzf = Fzf::builder()
+//!     .delimiter("\t")
+//!     .with_nth([FieldRange::from(2..)])
+//!     .nth([1])
+//!     .build()
+//!     .unwrap();
+//!
+//! let mut session = fzf.run().expect("Failed to start fzf");
+//! session.add_items(records).expect("Failed to add items");
+//!
+//! if let Ok(FzfOutcome::Selected(fields)) = session.output_fields() {
+//!     println!("Selected {} with the id {}", fields[1], fields[0]);
+//! }
+//! ```
+//!
+//! Fields separated by a regular expression need the `regex` feature, which adds
+//! `Delimiter::regex()`. `fzf` reads the expression with Go's syntax, so anything that rust's
+//! `regex` crate supports but Go doesn't, such as `\b{start}`, is rejected when it's created.
+//!
+//! ```toml
+//! [dependencies]
+//! fzf-wrapped = { version = "0.1", features = ["regex"] }
+//! ```
+//!
+//! ## Running `fzf` asynchronously
+//!
+//! If your program is built on `tokio`, enabling the `tokio` feature adds the `run_async()` method
+//! to [`Fzf`], which spawns `fzf` using `tokio::process` and returns an `AsyncFzfSession`. Starting
+//! `fzf`, adding items and waiting for the output are all `async`, so the executor is never
+//! blocked. Items can also be taken from any `futures::Stream` using the `add_stream()` method,
+//! meaning results from a slow async api are shown in `fzf` as soon as they arrive. It returns as
+//! soon as `fzf` exits, so the user can make their selection before the stream has ended.
+//!
+//! ```toml
+//! fzf-wrapped = { version = "0.1", features = ["tokio"] }
+//! ```
+//!
+//! ```ignore
+//! let mut session = Fzf::default().run_async().await?;
+//! session.add_stream(colours).await?;
+//! let users_selection = session.output().await?;
+//! ```
+//!
+//! See `examples/async_stream.rs` for the full example.
+//!
 //! ## Adding Items at runtime of `fzf`
 //!
 //! With the power of this library, you can use `fzf` to select from a list of items, even if those
-//! items have not been fetched yet. Using the `add_item` and `add_items` method adds items to 
-//! `fzf`'s list, even while fzf is running. This means that if you're calling information from a 
-//! REST api, you can display result's as they come in straight to `fzf`, or even hide the slight 
+//! items have not been fetched yet. Using the `add_item` and `add_items` method adds items to
+//! `fzf`'s list, even while fzf is running. This means that if you're calling information from a
+//! REST api, you can display result's as they come in straight to `fzf`, or even hide the slight
 //! delay by starting up `fzf`.
 //!
 //! For an example of this, look at my [workflows](https://github.com/danielronalds/workflows) project
-
+//!
+//! `add_items` writes every item before returning though, so with a slow or endless source of
+//! items the user's selection can't be returned until the source is exhausted. To get around this,
+//! the `feed()` method on [`FzfSession`] takes an iterator and writes it to `fzf` from a background
+//! thread, and the `sender()` method returns an [`ItemSender`] which can be cloned and moved to
+//! your own worker threads. Either way `output()` returns as soon as the user makes their
+//! selection, and the producers are told to stop.
+//!
+//! ```no_run
+//! use fzf_wrapped::{Fzf, FzfOutcome};
+//!
+//! fn main() {
+//!     let mut session = Fzf::default().run().expect("Failed to start fzf");
+//!
+//!     // An endless iterator, which will stop being polled once the user has made their selection
+//!     session.feed((1..).map(|x| x.to_string())).expect("Failed to feed fzf");
+//!
+//!     if let Ok(FzfOutcome::Selected(number)) = session.output() {
+//!         println!("You picked {}", number);
+//!     }
+//! }
+//! ```
 
 mod options;
 pub use options::*;
 mod fzf;
 pub use fzf::*;
+mod item;
+pub use item::FzfItem;
+mod outcome;
+pub use outcome::{Accepted, FzfOutcome, Selection};
+mod key;
+pub use key::Key;
+mod action;
+pub use action::Action;
+mod callback;
+pub use callback::handle_self_invocation;
+mod color;
+pub use color::{
+    AnsiColor, ColorAttr, ColorElement, ColorScheme, ColorSchemeBuilder, ColorSpec, ColorValue,
+};
+mod theme;
+pub use theme::ThemePreset;
+mod preview;
+pub use preview::{PreviewPosition, PreviewWindow, PreviewWindowBuilder};
+#[cfg(feature = "listen")]
+mod listen;
+#[cfg(feature = "listen")]
+pub use listen::{FzfClient, FzfState, FzfStateItem, Listen};
+mod error;
+pub use error::FzfError;
+mod executable;
+pub use executable::{find_fzf, FZF_BIN_ENV};
+mod version;
+pub use version::{fzf_version, FzfVersion, VersionPolicy};
+mod session;
+pub use session::FzfSession;
+mod feeder;
+pub use feeder::ItemSender;
+#[cfg(feature = "tokio")]
+mod async_session;
+#[cfg(feature = "tokio")]
+pub use async_session::AsyncFzfSession;
+
+use std::borrow::Borrow;
+#[cfg(unix)]
+use std::sync::{Arc, Mutex, PoisonError};
 
 /// Runs the given [`Fzf`] struct and returns the user's selection as a [`String`]
 ///
 /// # Parameters
 ///
+/// - `fzf` The configuration to run `fzf` with, which can be borrowed to reuse it
+/// - `items` The items to to display in `fzf`
+///
+/// # Returns
+///
+/// An [`FzfOutcome`] containing the users selection as a [`String`] if they selected one, or an
+/// [`FzfError`] if `fzf` could not be run
+pub fn run_with_output(
+    fzf: impl Borrow<Fzf>,
+    items: impl IntoIterator<Item = impl Into<String>>,
+) -> Result<FzfOutcome<String>, FzfError> {
+    let mut session = fzf.borrow().run()?;
+    session.add_items(items)?;
+    session.output()
+}
+
+/// Runs the given [`Fzf`] struct and returns each of the user's selections as a [`String`]
+///
+/// Multi-select must be enabled with the `multi` option for the user to be able to pick more than
+/// one item
+///
+/// # Parameters
+///
+/// - `fzf` The configuration to run `fzf` with, which can be borrowed to reuse it
+/// - `items` The items to to display in `fzf`
+///
+/// # Returns
+///
+/// An [`FzfOutcome`] containing the users selections as a [`Vec`] of [`String`]'s if they selected
+/// any, or an [`FzfError`] if `fzf` could not be run
+pub fn run_with_output_multi(
+    fzf: impl Borrow<Fzf>,
+    items: impl IntoIterator<Item = impl Into<String>>,
+) -> Result<FzfOutcome<Vec<String>>, FzfError> {
+    let mut session = fzf.borrow().run()?;
+    session.add_items(items)?;
+    session.output_multi()
+}
+
+/// Runs the given [`Fzf`] struct with the given items, and returns the item the user selected
+///
+/// Unlike `run_with_output`, the original value is returned rather than the text displayed in
+/// `fzf`, so items with the same text can still be told apart
+///
+/// # Parameters
+///
+/// - `fzf` The configuration to run `fzf` with, which can be borrowed to reuse it
+/// - `items` The items to to display in `fzf`
+///
+/// # Returns
+///
+/// An [`FzfOutcome`] containing the item the user selected if they selected one, or an
+/// [`FzfError`] if `fzf` could not be run
+pub fn run_with_selection<T: FzfItem>(
+    fzf: impl Borrow<Fzf>,
+    items: impl IntoIterator<Item = T>,
+) -> Result<FzfOutcome<T>, FzfError> {
+    run_with_selection_by(fzf, items, T::fzf_text)
+}
+
+/// Runs the given [`Fzf`] struct with the given items, displaying each item with the text
+/// returned by `text`, and returns the item the user selected
+///
+/// Works the same as `run_with_selection`, for types that don't implement [`FzfItem`], or that
+/// should be shown differently to their [`FzfItem`] text
+///
+/// # Parameters
+///
+/// - `fzf` The configuration to run `fzf` with, which can be borrowed to reuse it
+/// - `items` The items to to display in `fzf`
+/// - `text` The closure creating the text to display for an item
+///
+/// # Returns
+///
+/// An [`FzfOutcome`] containing the item the user selected if they selected one, or an
+/// [`FzfError`] if `fzf` could not be run
+pub fn run_with_selection_by<T>(
+    fzf: impl Borrow<Fzf>,
+    items: impl IntoIterator<Item = T>,
+    text: impl Fn(&T) -> String,
+) -> Result<FzfOutcome<T>, FzfError> {
+    Ok(
+        run_with_selection_multi_by(fzf, items, text)?.and_then(|selections| {
+            selections
+                .into_iter()
+                .next()
+                .map_or(FzfOutcome::NoMatch, FzfOutcome::Selected)
+        }),
+    )
+}
+
+/// Runs the given [`Fzf`] struct with the given items, and returns each of the items the user
+/// selected
+///
+/// Multi-select must be enabled with the `multi` option for the user to be able to pick more than
+/// one item
+///
+/// # Parameters
+///
+/// - `fzf` The configuration to run `fzf` with, which can be borrowed to reuse it
+/// - `items` The items to to display in `fzf`
+///
+/// # Returns
+///
+/// An [`FzfOutcome`] containing the items the user selected if they selected any, or an
+/// [`FzfError`] if `fzf` could not be run
+pub fn run_with_selection_multi<T: FzfItem>(
+    fzf: impl Borrow<Fzf>,
+    items: impl IntoIterator<Item = T>,
+) -> Result<FzfOutcome<Vec<T>>, FzfError> {
+    run_with_selection_multi_by(fzf, items, T::fzf_text)
+}
+
+/// Runs the given [`Fzf`] struct with the given items, displaying each item with the text
+/// returned by `text`, and returns each of the items the user selected
+///
+/// Works the same as `run_with_selection_multi`, for types that don't implement [`FzfItem`], or
+/// that should be shown differently to their [`FzfItem`] text
+///
+/// # Parameters
+///
+/// - `fzf` The configuration to run `fzf` with, which can be borrowed to reuse it
 /// - `items` The items to to display in `fzf`
+/// - `text` The closure creating the text to display for an item
 ///
 /// # Returns
 ///
-/// An option containing either the users selection as a [`String`], or `None` if the user quit `fzf`
-pub fn run_with_output(fzf: Fzf, items: impl IntoIterator<Item = impl Into<String>>) -> Option<String> {
-    let mut fzf = fzf;
-    fzf.run().ok()?;
-    fzf.add_items(items).ok()?;
-    fzf.output()
+/// An [`FzfOutcome`] containing the items the user selected if they selected any, or an
+/// [`FzfError`] if `fzf` could not be run
+pub fn run_with_selection_multi_by<T>(
+    fzf: impl Borrow<Fzf>,
+    items: impl IntoIterator<Item = T>,
+    text: impl Fn(&T) -> String,
+) -> Result<FzfOutcome<Vec<T>>, FzfError> {
+    let items: Vec<T> = items.into_iter().collect();
+    let mut session = fzf.borrow().run_indexed()?;
+    session.add_items(
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| item::indexed_line(i, &text(item))),
+    )?;
+    Ok(session.output_multi()?.map(|selections| {
+        let indexes = selections.iter().filter_map(|x| item::parse_index(x));
+        item::take_items(items, indexes)
+    }))
+}
+
+/// Runs the given [`Fzf`] struct with the given items, showing the text returned by `preview` for
+/// the focused item in the preview window, and returns each of the items the user selected
+///
+/// `fzf` runs the current executable to call `preview`, so [`handle_self_invocation`] must be
+/// called at the start of `main`. Only available on unix, like the `preview_fn` option
+///
+/// # Parameters
+///
+/// - `fzf` The configuration to run `fzf` with, which can be borrowed to reuse it
+/// - `items` The items to to display in `fzf`
+/// - `preview` The closure creating the preview of an item
+///
+/// # Returns
+///
+/// An [`FzfOutcome`] containing the items the user selected if they selected any, or an
+/// [`FzfError`] if `fzf` could not be run
+#[cfg(unix)]
+pub fn run_with_preview<T, F>(
+    fzf: impl Borrow<Fzf>,
+    items: impl IntoIterator<Item = T>,
+    preview: F,
+) -> Result<FzfOutcome<Vec<T>>, FzfError>
+where
+    T: FzfItem + Send + Sync + 'static,
+    F: Fn(&T) -> String + Send + Sync + 'static,
+{
+    let items: Vec<T> = items.into_iter().collect();
+    let lines: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| item::indexed_line(i, &item.fzf_text()))
+        .collect();
+    // The selected items are taken out of the shared vec, so nothing depends on the preview
+    // closure having been dropped by the time `fzf` exits
+    let items: Arc<Mutex<Vec<Option<T>>>> =
+        Arc::new(Mutex::new(items.into_iter().map(Some).collect()));
+    let lookup = Arc::clone(&items);
+    let fzf = fzf.borrow().clone().with_preview_fn(move |line| {
+        let items = lookup.lock().unwrap_or_else(PoisonError::into_inner);
+        item::parse_index(line)
+            .and_then(|index| items.get(index)?.as_ref())
+            .map(&preview)
+            .unwrap_or_default()
+    });
+
+    let mut session = fzf.run_indexed()?;
+    session.add_items(lines)?;
+    let outcome = session.output_multi()?;
+
+    let mut items = items.lock().unwrap_or_else(PoisonError::into_inner);
+    Ok(outcome.map(|selections| {
+        selections
+            .iter()
+            .filter_map(|x| items.get_mut(item::parse_index(x)?)?.take())
+            .collect()
+    }))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selections_can_be_displayed_with_other_text() {
+        let stand_in = executable::stand_in("grep-port", "grep -m 1 'port 80'");
+        let fzf = Fzf::builder().executable(stand_in.path()).build().unwrap();
+
+        let outcome = run_with_selection_by(&fzf, [22, 80], |port| format!("port {}", port));
+        assert_eq!(outcome.unwrap(), FzfOutcome::Selected(80));
+    }
+
+    #[test]
+    fn previewed_selections_are_returned_while_the_preview_is_shared() {
+        let stand_in = executable::stand_in("grep-80", "grep '80'");
+        let fzf = Fzf::builder().executable(stand_in.path()).build().unwrap();
+
+        let outcome = run_with_preview(&fzf, [22, 80, 8080], |port| format!("port {}", port));
+        assert_eq!(outcome.unwrap(), FzfOutcome::Selected(vec![80, 8080]));
+    }
 }
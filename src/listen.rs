@@ -0,0 +1,346 @@
+//! This module contains the client for the http server `fzf` starts with the `listen` option,
+//! which lets the program control `fzf` while it's running
+//!
+//! Actions are sent with `POST /`, in the same format as `--bind` uses, and the state of `fzf` is
+//! read as json with `GET /`
+
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    path::PathBuf,
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::action::{self, Action};
+use crate::error::FzfError;
+
+/// The environment variable `fzf` reads the api key from
+pub(crate) const API_KEY_ENV: &str = "FZF_API_KEY";
+/// How long to wait for `fzf` to respond to a request before giving up on it
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Enum to represent where `fzf` listens for requests, set with the `listen` option
+pub enum Listen {
+    /// A port that's free when `fzf` is started
+    AnyPort,
+    /// The given port on localhost
+    Port(u16),
+    /// A unix socket at the given path, which needs a newer version of `fzf` and must end in
+    /// `.sock`
+    Socket(PathBuf),
+}
+
+impl Listen {
+    /// Checks that `fzf` will accept the socket path
+    pub(crate) fn validate(&self) -> Result<(), FzfError> {
+        match self {
+            Self::Socket(path) if !path.to_string_lossy().ends_with(".sock") => {
+                Err(FzfError::Builder(format!(
+                    "fzf only listens on unix sockets ending in .sock, not {}",
+                    path.display()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates the `--listen` argument, and a client that connects to it
+    pub(crate) fn launch(&self, api_key: Option<String>) -> Result<(String, FzfClient), FzfError> {
+        let endpoint = match self {
+            Self::AnyPort => Endpoint::Port(free_port().map_err(FzfError::Listen)?),
+            Self::Port(port) => Endpoint::Port(*port),
+            Self::Socket(path) => Endpoint::Socket(path.clone()),
+        };
+        let arg = match &endpoint {
+            Endpoint::Port(port) => format!("--listen={}", port),
+            Endpoint::Socket(path) => format!("--listen={}", path.to_string_lossy()),
+        };
+        Ok((arg, FzfClient { endpoint, api_key }))
+    }
+}
+
+/// Finds a port on localhost that nothing is listening on
+///
+/// The port is released before `fzf` binds it, so another process could take it in between
+fn free_port() -> io::Result<u16> {
+    Ok(TcpListener::bind(("127.0.0.1", 0))?.local_addr()?.port())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Where the client sends its requests
+enum Endpoint {
+    Port(u16),
+    Socket(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Struct that sends requests to a running instance of `fzf`, created by
+/// [`crate::FzfSession::client`]
+///
+/// The client can be cloned and moved to another thread, so that it can be used while the session
+/// waits for the user to make their selection
+pub struct FzfClient {
+    endpoint: Endpoint,
+    api_key: Option<String>,
+}
+
+impl FzfClient {
+    /// Runs the chain of actions in `fzf`, as if they were bound to a key the user pressed
+    ///
+    /// # Parameters
+    ///
+    /// - `actions` The actions to run, in order
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::InvalidBind`] if there are no actions or they can't be chained, or
+    /// [`FzfError::Listen`] if `fzf` could not be reached, didn't respond within five seconds, or
+    /// rejected the request
+    pub fn post(&self, actions: impl IntoIterator<Item = Action>) -> Result<(), FzfError> {
+        let actions: Vec<Action> = actions.into_iter().collect();
+        if actions.is_empty() {
+            return Err(FzfError::InvalidBind("no actions to post".to_string()));
+        }
+        self.request("POST /", &action::chain(&actions)?)?;
+        Ok(())
+    }
+
+    /// Replaces the query
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `post` method
+    pub fn change_query(&self, query: impl Into<String>) -> Result<(), FzfError> {
+        self.post([Action::ChangeQuery(query.into())])
+    }
+
+    /// Replaces the items with the output of the given shell command
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `post` method
+    pub fn reload(&self, command: impl Into<String>) -> Result<(), FzfError> {
+        self.post([Action::Reload(command.into())])
+    }
+
+    /// Replaces the header
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `post` method
+    pub fn change_header(&self, header: impl Into<String>) -> Result<(), FzfError> {
+        self.post([Action::ChangeHeader(header.into())])
+    }
+
+    /// Moves the cursor to the given position in the list, counting from `1`, or from the end of
+    /// the list if negative
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `post` method
+    pub fn pos(&self, position: i32) -> Result<(), FzfError> {
+        self.post([Action::Pos(position)])
+    }
+
+    /// Toggles the selection of every matching item, when the `multi` option is enabled
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `post` method
+    pub fn toggle_all(&self) -> Result<(), FzfError> {
+        self.post([Action::ToggleAll])
+    }
+
+    /// Gets the current state of `fzf`, with up to the first 100 matches
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::Listen`] if `fzf` could not be reached, didn't respond within five
+    /// seconds, rejected the request, or its response could not be parsed
+    pub fn state(&self) -> Result<FzfState, FzfError> {
+        self.get("GET /")
+    }
+
+    /// Gets the current state of `fzf`, with a page of its matches
+    ///
+    /// # Parameters
+    ///
+    /// - `offset` The number of matches to skip
+    /// - `limit` The maximum number of matches to include
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `state` method
+    pub fn state_page(&self, offset: usize, limit: usize) -> Result<FzfState, FzfError> {
+        self.get(&format!("GET /?offset={}&limit={}", offset, limit))
+    }
+
+    /// Sends a request without a body, parsing the json response
+    fn get(&self, target: &str) -> Result<FzfState, FzfError> {
+        let body = self.request(target, "")?;
+        serde_json::from_str(&body)
+            .map_err(|e| FzfError::Listen(io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+
+    /// Sends a request with the given method and path, returning the body of the response
+    fn request(&self, target: &str, body: &str) -> Result<String, FzfError> {
+        let mut request = format!("{} HTTP/1.1\r\nHost: localhost\r\n", target);
+        if let Some(key) = &self.api_key {
+            request.push_str(&format!("x-api-key: {}\r\n", key));
+        }
+        request.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+
+        let response = self
+            .exchange(request.as_bytes())
+            .map_err(FzfError::Listen)?;
+        let response = String::from_utf8_lossy(&response);
+        let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+        let status = head.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some("200") => Ok(body.to_string()),
+            _ => Err(FzfError::Listen(io::Error::other(format!(
+                "{}: {}",
+                status,
+                body.trim()
+            )))),
+        }
+    }
+
+    /// Connects to `fzf`, sends the request and reads the whole response
+    fn exchange(&self, request: &[u8]) -> io::Result<Vec<u8>> {
+        match &self.endpoint {
+            Endpoint::Port(port) => {
+                let stream = TcpStream::connect(("localhost", *port))?;
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                exchange(stream, request, |x| x.shutdown(Shutdown::Write))
+            }
+            #[cfg(unix)]
+            Endpoint::Socket(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                exchange(stream, request, |x| x.shutdown(Shutdown::Write))
+            }
+            #[cfg(not(unix))]
+            Endpoint::Socket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "unix sockets are only supported on unix",
+            )),
+        }
+    }
+}
+
+/// Writes the request to the stream, closing its write half so the server knows it has ended,
+/// then reads the response
+fn exchange<S: Read + Write>(
+    mut stream: S,
+    request: &[u8],
+    shutdown: impl Fn(&S) -> io::Result<()>,
+) -> io::Result<Vec<u8>> {
+    stream.write_all(request)?;
+    shutdown(&stream)?;
+    let mut response = vec![];
+    stream.read_to_end(&mut response)?;
+    Ok(response)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+/// Struct that represents the state of a running instance of `fzf`, returned by
+/// [`FzfClient::state`]
+pub struct FzfState {
+    /// Whether `fzf` is still reading items
+    pub reading: bool,
+    /// How far through matching the items `fzf` is, as a percentage
+    pub progress: u8,
+    /// The current query
+    pub query: String,
+    /// The position of the cursor in the list, counting from `0`
+    pub position: usize,
+    /// Whether the matches are sorted by score
+    pub sort: bool,
+    /// The number of items `fzf` has read
+    pub total_count: usize,
+    /// The number of items matching the query
+    pub match_count: usize,
+    /// The item under the cursor, if anything matches
+    pub current: Option<FzfStateItem>,
+    /// The page of matching items that was asked for
+    pub matches: Vec<FzfStateItem>,
+    /// The selected items, when the `multi` option is enabled
+    pub selected: Vec<FzfStateItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default)]
+/// Struct that represents an item in [`FzfState`]
+pub struct FzfStateItem {
+    /// The position the item was read in, counting from `0`
+    pub index: usize,
+    /// The line `fzf` read for the item
+    pub text: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    /// Answers a single request with the given response, returning the request it received
+    fn stand_in(response: &'static str) -> (FzfClient, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let thread = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            stream.read_to_string(&mut request).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+            request
+        });
+        let (_, client) = Listen::Port(port)
+            .launch(Some("secret".to_string()))
+            .unwrap();
+        (client, thread)
+    }
+
+    #[test]
+    fn actions_are_posted_with_the_api_key() {
+        let (client, server) = stand_in("HTTP/1.1 200 OK\r\n\r\n");
+        client
+            .post([Action::ChangeQuery("foo".to_string()), Action::ToggleAll])
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST / HTTP/1.1\r\n"));
+        assert!(request.contains("x-api-key: secret\r\n"));
+        assert!(request.ends_with("\r\n\r\nchange-query(foo)+toggle-all"));
+
+        let (client, server) = stand_in("HTTP/1.1 401 Unauthorized\r\n\r\ninvalid api key");
+        assert!(matches!(client.pos(3), Err(FzfError::Listen(_))));
+        assert!(server.join().unwrap().ends_with("\r\n\r\npos(3)"));
+    }
+
+    #[test]
+    fn state_is_parsed() {
+        let (client, server) = stand_in(concat!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n",
+            r#"{"reading":false,"progress":100,"query":"re","position":0,"sort":true,"#,
+            r#""totalCount":3,"matchCount":2,"current":{"index":0,"text":"red"},"#,
+            r#""matches":[{"index":0,"text":"red"},{"index":2,"text":"green"}],"selected":[]}"#
+        ));
+        let state = client.state_page(0, 10).unwrap();
+
+        assert!(server
+            .join()
+            .unwrap()
+            .starts_with("GET /?offset=0&limit=10 HTTP/1.1\r\n"));
+        assert_eq!(state.query, "re");
+        assert_eq!((state.total_count, state.match_count), (3, 2));
+        assert_eq!(state.current.map(|x| x.text), Some("red".to_string()));
+        assert_eq!(state.matches.len(), 2);
+        assert!(state.selected.is_empty());
+    }
+}
@@ -2,28 +2,33 @@
 
 // TODO: Impelemnt TryFrom trait for options
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+use std::{
+    fmt,
+    ops::{RangeFrom, RangeFull, RangeInclusive, RangeToInclusive},
+};
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use crate::error::FzfError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 /// Enum to represent the scoring schemes fzf can use
 pub enum Scheme {
+    #[default]
     Default,
     Path,
     History,
 }
 
-impl Default for Scheme {
-    fn default() -> Self {
-        Self::Default
-    }
-}
-
-impl ToString for Scheme {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
             Self::Default => "default",
             Self::Path => "path",
             Self::History => "history",
-        }
-        .to_string()
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -38,30 +43,25 @@ impl From<String> for Scheme {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 /// Enum to represent the different themes fzf can have
 pub enum Color {
+    #[default]
     Dark,
     Light,
     Sixteen,
     Bw,
 }
 
-impl Default for Color {
-    fn default() -> Self {
-        Self::Dark
-    }
-}
-
-impl ToString for Color {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
             Self::Dark => "dark",
             Self::Light => "light",
             Self::Sixteen => "16",
             Self::Bw => "bw",
-        }
-        .to_string()
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -77,28 +77,23 @@ impl From<String> for Color {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 /// An Enum to represent the possible layouts to display `fzf` with
 pub enum Layout {
+    #[default]
     Default,
     Reverse,
     ReverseList,
 }
 
-impl Default for Layout {
-    fn default() -> Self {
-        Self::Default
-    }
-}
-
-impl ToString for Layout {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
             Layout::Default => "default",
             Layout::Reverse => "reverse",
             Layout::ReverseList => "reverse-list",
-        }
-        .to_string()
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -113,40 +108,49 @@ impl From<String> for Layout {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 /// An Enum to represent the possible borders to display around the finder
 pub enum Border {
+    #[default]
     None,
     Rounded,
     Sharp,
+    Bold,
+    Double,
+    /// Uses block elements, which look best with a background color
+    Block,
+    /// Uses thin block elements, which look best with a background color
+    ThinBlock,
     Horizontal,
     Vertical,
+    /// A single line between sections, only supported by the input, list and header borders, so
+    /// the `border` option rejects it
+    Line,
     Top,
     Bottom,
     Left,
     Right,
 }
 
-impl Default for Border {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
-impl ToString for Border {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for Border {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
             Border::None => "none",
             Border::Rounded => "rounded",
             Border::Sharp => "sharp",
+            Border::Bold => "bold",
+            Border::Double => "double",
+            Border::Block => "block",
+            Border::ThinBlock => "thinblock",
             Border::Horizontal => "horizontal",
             Border::Vertical => "vertical",
+            Border::Line => "line",
             Border::Top => "top",
             Border::Bottom => "bottom",
             Border::Left => "left",
             Border::Right => "right",
-        }
-        .to_string()
+        };
+        write!(f, "{}", value)
     }
 }
 
@@ -156,8 +160,13 @@ impl From<String> for Border {
             "none" => Border::None,
             "rounded" => Border::Rounded,
             "sharp" => Border::Sharp,
+            "bold" => Border::Bold,
+            "double" => Border::Double,
+            "block" => Border::Block,
+            "thinblock" => Border::ThinBlock,
             "horizontal" => Border::Horizontal,
             "vertical" => Border::Vertical,
+            "line" => Border::Line,
             "top" => Border::Top,
             "bottom" => Border::Bottom,
             "left" => Border::Left,
@@ -166,3 +175,666 @@ impl From<String> for Border {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent how many items the user is allowed to select
+pub enum Multi {
+    /// Only a single item can be selected
+    #[default]
+    Disabled,
+    /// Any number of items can be selected
+    Unlimited,
+    /// At most the given number of items can be selected
+    Limit(usize),
+}
+
+impl From<usize> for Multi {
+    fn from(value: usize) -> Self {
+        Self::Limit(value)
+    }
+}
+
+impl From<bool> for Multi {
+    fn from(value: bool) -> Self {
+        match value {
+            true => Self::Unlimited,
+            false => Self::Disabled,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent the order multiple selections are returned in
+pub enum MultiOrder {
+    /// The order the user picked the items in
+    #[default]
+    Picked,
+    /// The order the items were given to `fzf`
+    Input,
+}
+
+impl fmt::Display for MultiOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Picked => "picked",
+            Self::Input => "input",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl From<String> for MultiOrder {
+    fn from(value: String) -> Self {
+        match value.to_lowercase().as_str() {
+            "picked" => Self::Picked,
+            "input" => Self::Input,
+            _ => Self::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent how `fzf` treats the user's `FZF_DEFAULT_OPTS`, `FZF_DEFAULT_OPTS_FILE` and
+/// `FZF_DEFAULT_COMMAND` environment variables
+pub enum DefaultOptsPolicy {
+    /// Pass the environment variables on to `fzf` untouched
+    #[default]
+    Inherit,
+    /// Remove the environment variables, so `fzf` only uses the configured options
+    Ignore,
+    /// Keep the user's options, but reset the ones that change what `fzf` prints, such as
+    /// `--multi`, `--print-query`, `--preview` and `--accept-nth`, before applying the configured
+    /// options
+    Merge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent a size, as either a number of lines or columns, or a percentage of the space
+/// available
+pub enum Size {
+    /// A number of lines, or columns
+    Lines(u16),
+    /// A percentage of the space available, up to `100`
+    Percent(u8),
+}
+
+impl Size {
+    /// Checks that a percentage is no more than the space available
+    pub(crate) fn validate(self) -> Result<(), FzfError> {
+        match self {
+            Self::Percent(percent) if percent > 100 => Err(FzfError::Builder(format!(
+                "{}% is more than the space available",
+                percent
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lines(lines) => write!(f, "{}", lines),
+            Self::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl From<u16> for Size {
+    fn from(value: u16) -> Self {
+        Self::Lines(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent the height of `fzf`, which is shown below the cursor instead of taking up
+/// the whole screen
+pub enum Height {
+    /// Always takes up the given size
+    Fixed(Size),
+    /// Shrinks to fit the items when there are only a few of them, up to the given size
+    Adaptive(Size),
+}
+
+impl Height {
+    /// Gets the size, whether it's fixed or adaptive
+    pub(crate) fn size(self) -> Size {
+        match self {
+            Self::Fixed(size) | Self::Adaptive(size) => size,
+        }
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(size) => write!(f, "{}", size),
+            Self::Adaptive(size) => write!(f, "~{}", size),
+        }
+    }
+}
+
+impl From<Size> for Height {
+    fn from(value: Size) -> Self {
+        Self::Fixed(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent the space around `fzf` for the `margin` and `padding` options, following
+/// the same order as CSS
+///
+/// Can be created from a [`Size`], or a tuple of two to four sizes
+pub enum Spacing {
+    /// The same space on every side
+    All(Size),
+    /// The space above and below, then the space on the left and right
+    Axes(Size, Size),
+    /// The space above, then on the left and right, then below
+    TopSidesBottom(Size, Size, Size),
+    /// The space above, on the right, below, then on the left
+    Sides(Size, Size, Size, Size),
+}
+
+impl Spacing {
+    /// Gets the size of every side that was given
+    pub(crate) fn sizes(self) -> Vec<Size> {
+        match self {
+            Self::All(all) => vec![all],
+            Self::Axes(vertical, horizontal) => vec![vertical, horizontal],
+            Self::TopSidesBottom(top, sides, bottom) => vec![top, sides, bottom],
+            Self::Sides(top, right, bottom, left) => vec![top, right, bottom, left],
+        }
+    }
+}
+
+impl fmt::Display for Spacing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All(all) => write!(f, "{}", all),
+            Self::Axes(vertical, horizontal) => write!(f, "{},{}", vertical, horizontal),
+            Self::TopSidesBottom(top, sides, bottom) => write!(f, "{},{},{}", top, sides, bottom),
+            Self::Sides(top, right, bottom, left) => {
+                write!(f, "{},{},{},{}", top, right, bottom, left)
+            }
+        }
+    }
+}
+
+impl From<Size> for Spacing {
+    fn from(value: Size) -> Self {
+        Self::All(value)
+    }
+}
+
+impl<A: Into<Size>, B: Into<Size>> From<(A, B)> for Spacing {
+    fn from((vertical, horizontal): (A, B)) -> Self {
+        Self::Axes(vertical.into(), horizontal.into())
+    }
+}
+
+impl<A: Into<Size>, B: Into<Size>, C: Into<Size>> From<(A, B, C)> for Spacing {
+    fn from((top, sides, bottom): (A, B, C)) -> Self {
+        Self::TopSidesBottom(top.into(), sides.into(), bottom.into())
+    }
+}
+
+impl<A, B, C, D> From<(A, B, C, D)> for Spacing
+where
+    A: Into<Size>,
+    B: Into<Size>,
+    C: Into<Size>,
+    D: Into<Size>,
+{
+    fn from((top, right, bottom, left): (A, B, C, D)) -> Self {
+        Self::Sides(top.into(), right.into(), bottom.into(), left.into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent where `fzf` shows the info line, with the match counter
+pub enum Info {
+    /// On its own line, below the prompt
+    #[default]
+    Default,
+    /// On its own line, aligned to the right
+    Right,
+    /// Not shown
+    Hidden,
+    /// After the query, on the prompt line
+    Inline,
+    /// At the right of the prompt line
+    InlineRight,
+}
+
+impl fmt::Display for Info {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Default => "default",
+            Self::Right => "right",
+            Self::Hidden => "hidden",
+            Self::Inline => "inline",
+            Self::InlineRight => "inline-right",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl From<String> for Info {
+    fn from(value: String) -> Self {
+        match value.to_lowercase().as_str() {
+            "default" => Self::Default,
+            "right" => Self::Right,
+            "hidden" => Self::Hidden,
+            "inline" => Self::Inline,
+            "inline-right" => Self::InlineRight,
+            _ => Self::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent where a label sits along its border line
+pub enum LabelAlign {
+    /// In the middle of the border line
+    #[default]
+    Center,
+    /// The given number of columns from the left, where `1` is the leftmost column
+    ///
+    /// `0` isn't allowed, as `fzf` centres labels given it
+    Left(u16),
+    /// The given number of columns from the right, where `1` is the rightmost column
+    ///
+    /// `0` isn't allowed, as `fzf` centres labels given it
+    Right(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Struct that represents the position of a border label, such as the one set with
+/// `border_label`
+///
+/// Can be created from a [`LabelAlign`], which puts the label on the top border line
+pub struct LabelPosition {
+    /// Where the label sits along the border line
+    pub align: LabelAlign,
+    /// Put the label on the bottom border line, rather than the top one
+    pub bottom: bool,
+}
+
+impl LabelPosition {
+    /// Creates a position on the top border line
+    pub fn top(align: LabelAlign) -> Self {
+        Self {
+            align,
+            bottom: false,
+        }
+    }
+
+    /// Creates a position on the bottom border line
+    pub fn bottom(align: LabelAlign) -> Self {
+        Self {
+            align,
+            bottom: true,
+        }
+    }
+}
+
+impl LabelPosition {
+    /// Checks that the label isn't aligned to column `0`, which `fzf` takes as the centre
+    pub(crate) fn validate(self) -> Result<(), FzfError> {
+        match self.align {
+            LabelAlign::Left(0) | LabelAlign::Right(0) => Err(FzfError::Builder(
+                "label columns are counted from 1, use LabelAlign::Center to centre it".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for LabelPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.align {
+            LabelAlign::Center => write!(f, "0")?,
+            LabelAlign::Left(columns) => write!(f, "{}", columns)?,
+            LabelAlign::Right(columns) => write!(f, "-{}", columns)?,
+        }
+        if self.bottom {
+            write!(f, ":bottom")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<LabelAlign> for LabelPosition {
+    fn from(value: LabelAlign) -> Self {
+        Self::top(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent the presets `fzf` has for the look of its borders and sections
+pub enum Style {
+    #[default]
+    Default,
+    /// Borders and separators kept to a minimum
+    Minimal,
+    /// Borders around the input, list and header, as well as the preview window
+    Full,
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Default => "default",
+            Self::Minimal => "minimal",
+            Self::Full => "full",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl From<String> for Style {
+    fn from(value: String) -> Self {
+        match value.to_lowercase().as_str() {
+            "default" => Self::Default,
+            "minimal" => Self::Minimal,
+            "full" => Self::Full,
+            _ => Self::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent a field index expression, used by the `nth`, `with_nth` and `accept_nth`
+/// options
+///
+/// Fields are counted from `1`, and negative indexes count back from the last field, so `-1` is
+/// the last field. Ranges include both ends, and can be created from rust's inclusive ranges,
+/// such as `2..` or `..=-2`
+pub enum FieldRange {
+    /// A single field
+    Field(i32),
+    /// The given field and every field after it
+    From(i32),
+    /// Every field up to and including the given one
+    To(i32),
+    /// Every field from the first index up to and including the second
+    Between(i32, i32),
+    /// Every field
+    All,
+}
+
+impl FieldRange {
+    /// Whether the expression uses the field index `0`, which `fzf` rejects
+    pub(crate) fn has_zero(&self) -> bool {
+        match *self {
+            Self::Field(x) | Self::From(x) | Self::To(x) => x == 0,
+            Self::Between(start, end) => start == 0 || end == 0,
+            Self::All => false,
+        }
+    }
+
+    /// Moves the expression past the given number of leading fields, leaving indexes counted
+    /// from the last field alone
+    pub(crate) fn shifted(self, fields: i32) -> Self {
+        let shift = |x: i32| if x > 0 { x + fields } else { x };
+        match self {
+            Self::Field(x) => Self::Field(shift(x)),
+            Self::From(x) => Self::From(shift(x)),
+            Self::To(x) => Self::Between(1 + fields, shift(x)),
+            Self::Between(start, end) => Self::Between(shift(start), shift(end)),
+            Self::All => Self::From(1 + fields),
+        }
+    }
+}
+
+impl fmt::Display for FieldRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field(field) => write!(f, "{}", field),
+            Self::From(start) => write!(f, "{}..", start),
+            Self::To(end) => write!(f, "..{}", end),
+            Self::Between(start, end) => write!(f, "{}..{}", start, end),
+            Self::All => write!(f, ".."),
+        }
+    }
+}
+
+impl From<i32> for FieldRange {
+    fn from(value: i32) -> Self {
+        Self::Field(value)
+    }
+}
+
+impl From<RangeFrom<i32>> for FieldRange {
+    fn from(value: RangeFrom<i32>) -> Self {
+        Self::From(value.start)
+    }
+}
+
+impl From<RangeToInclusive<i32>> for FieldRange {
+    fn from(value: RangeToInclusive<i32>) -> Self {
+        Self::To(value.end)
+    }
+}
+
+impl From<RangeInclusive<i32>> for FieldRange {
+    fn from(value: RangeInclusive<i32>) -> Self {
+        Self::Between(*value.start(), *value.end())
+    }
+}
+
+impl From<RangeFull> for FieldRange {
+    fn from(_: RangeFull) -> Self {
+        Self::All
+    }
+}
+
+/// Joins field index expressions into the comma separated list `fzf` takes
+pub(crate) fn field_list(fields: &[FieldRange]) -> String {
+    let fields: Vec<String> = fields.iter().map(ToString::to_string).collect();
+    fields.join(",")
+}
+
+#[derive(Debug, Clone)]
+/// Enum to represent what separates the fields of each item, instead of `fzf`'s default of
+/// splitting on whitespace like AWK
+///
+/// Can be created from a string, which is used as a literal delimiter
+pub enum Delimiter {
+    /// A string that is matched exactly, such as `"\t"` or `","`
+    Literal(String),
+    /// A regular expression, such as `"[:/]"`, created with [`Delimiter::regex`]
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+}
+
+impl Delimiter {
+    /// Creates a delimiter from a regular expression, which `fzf` reads with Go's syntax
+    ///
+    /// Syntax that rust supports but Go doesn't is rejected, such as character class set
+    /// operations, `\b{start}` and the `x` flag. Unlike rust, Go's `\d`, `\s` and `\w` only match
+    /// ASCII characters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::Builder`] if the regular expression is invalid, or uses syntax Go
+    /// doesn't support
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str) -> Result<Self, FzfError> {
+        let invalid = |e: String| FzfError::Builder(format!("invalid delimiter: {}", e));
+        go_syntax::check(pattern).map_err(invalid)?;
+        Regex::new(pattern)
+            .map(Self::Regex)
+            .map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Creates the regular expression `fzf` is passed
+    pub(crate) fn pattern(&self) -> String {
+        match self {
+            Self::Literal(literal) => quote_meta(literal),
+            #[cfg(feature = "regex")]
+            Self::Regex(regex) => regex.as_str().to_string(),
+        }
+    }
+
+    /// Splits a line into its fields, without the delimiters
+    pub(crate) fn split(&self, line: &str) -> Vec<String> {
+        match self {
+            Self::Literal(literal) => line.split(literal.as_str()).map(String::from).collect(),
+            #[cfg(feature = "regex")]
+            Self::Regex(regex) => regex.split(line).map(String::from).collect(),
+        }
+    }
+
+    /// Whether the delimiter is a regular expression, along with its text, which delimiters are
+    /// compared by
+    fn key(&self) -> (bool, &str) {
+        match self {
+            Self::Literal(literal) => (false, literal),
+            #[cfg(feature = "regex")]
+            Self::Regex(regex) => (true, regex.as_str()),
+        }
+    }
+}
+
+impl PartialEq for Delimiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Delimiter {}
+
+impl PartialOrd for Delimiter {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Delimiter {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl std::hash::Hash for Delimiter {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// Escapes the characters that are special in Go's regular expressions, like Go's
+/// `regexp.QuoteMeta`, so `fzf` matches the string exactly
+fn quote_meta(literal: &str) -> String {
+    let mut quoted = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if r"\.+*?()|[]{}^$".contains(c) {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted
+}
+
+/// Checks regular expressions for syntax that rust supports but Go, which `fzf` is written in,
+/// doesn't
+#[cfg(feature = "regex")]
+mod go_syntax {
+    use regex_syntax::ast::{
+        self, AssertionKind, Ast, ClassSetBinaryOp, ClassSetItem, Flag, Flags, FlagsItemKind,
+        GroupKind, HexLiteralKind, LiteralKind,
+    };
+
+    /// Returns a description of the first unsupported syntax the pattern uses
+    pub(super) fn check(pattern: &str) -> Result<(), String> {
+        let ast = ast::parse::Parser::new()
+            .parse(pattern)
+            .map_err(|e| e.to_string())?;
+        ast::visit(&ast, GoSyntax)
+    }
+
+    struct GoSyntax;
+
+    impl ast::Visitor for GoSyntax {
+        type Output = ();
+        type Err = String;
+
+        fn finish(self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn visit_pre(&mut self, ast: &Ast) -> Result<(), String> {
+            match ast {
+                Ast::Assertion(assertion) => match assertion.kind {
+                    AssertionKind::WordBoundaryStart
+                    | AssertionKind::WordBoundaryEnd
+                    | AssertionKind::WordBoundaryStartAngle
+                    | AssertionKind::WordBoundaryEndAngle
+                    | AssertionKind::WordBoundaryStartHalf
+                    | AssertionKind::WordBoundaryEndHalf => {
+                        unsupported("word boundaries other than \\b and \\B")
+                    }
+                    _ => Ok(()),
+                },
+                Ast::Literal(literal) => match literal.kind {
+                    LiteralKind::HexFixed(HexLiteralKind::X)
+                    | LiteralKind::HexBrace(HexLiteralKind::X) => Ok(()),
+                    LiteralKind::HexFixed(_) | LiteralKind::HexBrace(_) => {
+                        unsupported("\\u and \\U escapes")
+                    }
+                    _ => Ok(()),
+                },
+                Ast::Flags(flags) => check_flags(&flags.flags),
+                Ast::Group(group) => match &group.kind {
+                    GroupKind::NonCapturing(flags) => check_flags(flags),
+                    _ => Ok(()),
+                },
+                _ => Ok(()),
+            }
+        }
+
+        fn visit_class_set_item_pre(&mut self, item: &ClassSetItem) -> Result<(), String> {
+            match item {
+                ClassSetItem::Bracketed(_) => unsupported("nested character classes"),
+                _ => Ok(()),
+            }
+        }
+
+        fn visit_class_set_binary_op_pre(&mut self, _: &ClassSetBinaryOp) -> Result<(), String> {
+            unsupported("character class set operations")
+        }
+    }
+
+    /// Checks for the flags Go doesn't have
+    fn check_flags(flags: &Flags) -> Result<(), String> {
+        let flag = flags.items.iter().find_map(|x| match x.kind {
+            FlagsItemKind::Flag(flag @ (Flag::Unicode | Flag::CRLF | Flag::IgnoreWhitespace)) => {
+                Some(flag)
+            }
+            _ => None,
+        });
+        match flag {
+            Some(Flag::Unicode) => unsupported("the u flag"),
+            Some(Flag::CRLF) => unsupported("the R flag"),
+            Some(_) => unsupported("the x flag"),
+            None => Ok(()),
+        }
+    }
+
+    fn unsupported(syntax: &str) -> Result<(), String> {
+        Err(format!("{} aren't supported by fzf", syntax))
+    }
+}
+
+impl From<&str> for Delimiter {
+    fn from(value: &str) -> Self {
+        Self::Literal(value.to_string())
+    }
+}
+
+impl From<String> for Delimiter {
+    fn from(value: String) -> Self {
+        Self::Literal(value)
+    }
+}
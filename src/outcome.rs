@@ -0,0 +1,208 @@
+//! This module contains the [`FzfOutcome`] enum, which represents how an `fzf` session ended
+
+use std::process::ExitStatus;
+
+use crate::error::FzfError;
+use crate::key::Key;
+
+/// Exit code `fzf` uses when there was no match
+pub(crate) const EXIT_NO_MATCH: i32 = 1;
+/// Exit code `fzf` uses when there was an error
+const EXIT_ERROR: i32 = 2;
+/// Exit code `fzf` uses when it was interrupted with ctrl-c or esc
+const EXIT_INTERRUPTED: i32 = 130;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Enum to represent the ways an `fzf` session can end
+pub enum FzfOutcome<T> {
+    /// The user selected an item
+    Selected(T),
+    /// `fzf` selected the only matching item without showing its ui, because of the `select_1`
+    /// option. Only told apart from `Selected` on unix
+    AutoSelected(T),
+    /// The user quit `fzf` with ctrl-c or esc, exit code 130
+    Aborted,
+    /// No item matched the user's query when they accepted, exit code 1
+    NoMatch,
+    /// `fzf` exited without showing its ui because no items matched, because of the `exit_0`
+    /// option. Only told apart from `NoMatch` on unix
+    AutoExited,
+    /// `fzf` exited with an error, usually because of a bad argument, exit code 2
+    Failed {
+        /// The exit code of `fzf`, or `None` if it was terminated by a signal
+        code: Option<i32>,
+        /// Everything `fzf` wrote to stderr
+        stderr: String,
+    },
+}
+
+impl<T> FzfOutcome<T> {
+    /// Creates an outcome from everything `fzf` left behind when it exited
+    ///
+    /// `selection` is only called with `fzf`'s stdout if it exited successfully. Exit codes `fzf`
+    /// doesn't document are returned as [`FzfError::ExitCode`]
+    pub(crate) fn from_exit(
+        exit: FzfExit,
+        selection: impl FnOnce(Vec<u8>) -> Result<T, FzfError>,
+    ) -> Result<Self, FzfError> {
+        match (exit.status.code(), exit.interacted) {
+            (Some(0), true) => Ok(Self::Selected(selection(exit.stdout)?)),
+            (Some(0), false) => Ok(Self::AutoSelected(selection(exit.stdout)?)),
+            (Some(EXIT_NO_MATCH), true) => Ok(Self::NoMatch),
+            (Some(EXIT_NO_MATCH), false) => Ok(Self::AutoExited),
+            (Some(EXIT_INTERRUPTED), _) => Ok(Self::Aborted),
+            (Some(EXIT_ERROR), _) => Ok(Self::Failed {
+                code: Some(EXIT_ERROR),
+                stderr: exit.stderr,
+            }),
+            (code, _) => Err(FzfError::ExitCode {
+                code,
+                stderr: exit.stderr,
+            }),
+        }
+    }
+
+    /// Returns `true` if an item was selected, either by the user or automatically
+    pub fn is_selected(&self) -> bool {
+        matches!(self, Self::Selected(_) | Self::AutoSelected(_))
+    }
+
+    /// Returns `true` if `fzf` exited without showing its ui, because of the `select_1` or
+    /// `exit_0` options
+    pub fn is_automatic(&self) -> bool {
+        matches!(self, Self::AutoSelected(_) | Self::AutoExited)
+    }
+
+    /// Converts the outcome into an [`Option`], containing the selection if there was one
+    pub fn selected(self) -> Option<T> {
+        match self {
+            Self::Selected(selection) | Self::AutoSelected(selection) => Some(selection),
+            _ => None,
+        }
+    }
+
+    /// Maps the selection with the given function, leaving the other outcomes untouched
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> FzfOutcome<U> {
+        match self {
+            Self::AutoSelected(selection) => FzfOutcome::AutoSelected(f(selection)),
+            outcome => outcome.and_then(|selection| FzfOutcome::Selected(f(selection))),
+        }
+    }
+
+    /// Replaces the selection with the outcome returned by the given function, leaving the other
+    /// outcomes untouched
+    ///
+    /// An automatic selection stays automatic if the function selects something
+    pub fn and_then<U>(self, f: impl FnOnce(T) -> FzfOutcome<U>) -> FzfOutcome<U> {
+        match self {
+            Self::Selected(selection) => f(selection),
+            Self::AutoSelected(selection) => match f(selection) {
+                FzfOutcome::Selected(selection) => FzfOutcome::AutoSelected(selection),
+                FzfOutcome::NoMatch => FzfOutcome::AutoExited,
+                outcome => outcome,
+            },
+            Self::Aborted => FzfOutcome::Aborted,
+            Self::NoMatch => FzfOutcome::NoMatch,
+            Self::AutoExited => FzfOutcome::AutoExited,
+            Self::Failed { code, stderr } => FzfOutcome::Failed { code, stderr },
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Struct that holds everything `fzf` left behind once it exited
+pub(crate) struct FzfExit {
+    /// The exit status of `fzf`
+    pub(crate) status: ExitStatus,
+    /// Everything `fzf` printed to stdout
+    pub(crate) stdout: Vec<u8>,
+    /// The tail of everything `fzf` wrote to stderr
+    pub(crate) stderr: String,
+    /// Whether `fzf` showed its ui, which it skips when using the `select_1` or `exit_0` options
+    pub(crate) interacted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// Struct that represents everything `fzf` printed when the user made their selection
+pub struct Selection {
+    /// The query the user had typed, if the `print_query` option was enabled
+    pub query: Option<String>,
+    /// The key the user accepted with, if it was one of the keys given to the `expect` option
+    pub key: Option<Key>,
+    /// The items the user selected
+    pub items: Vec<String>,
+}
+
+impl Selection {
+    /// Gets whether the user accepted an item, or their query when nothing matched it
+    pub fn accepted(self) -> Accepted {
+        match self.items.into_iter().next() {
+            Some(item) => Accepted::Item(item),
+            None => Accepted::Query(self.query.unwrap_or_default()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Enum to represent what the user accepted when using the `accept_query` option
+pub enum Accepted {
+    /// The user accepted the focused item
+    Item(String),
+    /// Nothing matched, so the user accepted the query they typed
+    Query(String),
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    fn outcome_with(code: i32, interacted: bool) -> Result<FzfOutcome<&'static str>, FzfError> {
+        let exit = FzfExit {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: vec![],
+            stderr: "unknown option".to_string(),
+            interacted,
+        };
+        FzfOutcome::from_exit(exit, |_| Ok("red"))
+    }
+
+    fn outcome(code: i32) -> Result<FzfOutcome<&'static str>, FzfError> {
+        outcome_with(code, true)
+    }
+
+    #[test]
+    fn exit_codes_are_mapped_to_outcomes() {
+        assert_eq!(outcome(0).unwrap(), FzfOutcome::Selected("red"));
+        assert_eq!(outcome(1).unwrap(), FzfOutcome::NoMatch);
+        assert_eq!(outcome(130).unwrap(), FzfOutcome::Aborted);
+        assert_eq!(
+            outcome(2).unwrap(),
+            FzfOutcome::Failed {
+                code: Some(2),
+                stderr: "unknown option".to_string()
+            }
+        );
+        assert!(matches!(
+            outcome(127),
+            Err(FzfError::ExitCode {
+                code: Some(127),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn skipping_the_ui_is_reported() {
+        assert_eq!(
+            outcome_with(0, false).unwrap(),
+            FzfOutcome::AutoSelected("red")
+        );
+        assert_eq!(outcome_with(1, false).unwrap(), FzfOutcome::AutoExited);
+        assert_eq!(outcome_with(130, false).unwrap(), FzfOutcome::Aborted);
+        assert_eq!(
+            outcome_with(0, false).unwrap().map(str::len),
+            FzfOutcome::AutoSelected(3)
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! This module contains the [`PreviewWindow`] struct, which configures where and how `fzf` shows
+//! the output of the `preview` command
+
+use std::fmt;
+
+use derive_builder::Builder;
+
+use crate::error::FzfError;
+use crate::options::{Border, Size};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent the side of `fzf` the preview window is shown on
+pub enum PreviewPosition {
+    Up,
+    Down,
+    Left,
+    #[default]
+    Right,
+}
+
+impl fmt::Display for PreviewPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            PreviewPosition::Up => "up",
+            PreviewPosition::Down => "down",
+            PreviewPosition::Left => "left",
+            PreviewPosition::Right => "right",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl From<String> for PreviewPosition {
+    fn from(value: String) -> Self {
+        match value.to_lowercase().as_str() {
+            "up" | "top" => PreviewPosition::Up,
+            "down" | "bottom" => PreviewPosition::Down,
+            "left" => PreviewPosition::Left,
+            "right" => PreviewPosition::Right,
+            _ => PreviewPosition::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Builder, Default, PartialEq, Eq, Hash)]
+#[builder(build_fn(error = "FzfError", validate = "Self::validate"))]
+/// Struct that represents the layout of the preview window, passed to `fzf` with
+/// `--preview-window`
+///
+/// Options that aren't set are left to `fzf`'s defaults
+pub struct PreviewWindow {
+    /// The side of `fzf` to show the preview window on
+    #[builder(setter(into, strip_option), default = "None")]
+    position: Option<PreviewPosition>,
+    /// The size of the preview window
+    #[builder(setter(into, strip_option), default = "None")]
+    size: Option<Size>,
+    /// The border to draw around the preview window
+    #[builder(setter(into, strip_option), default = "None")]
+    border: Option<Border>,
+    /// Wrap long lines
+    #[builder(setter(into, strip_option), default = "false")]
+    wrap: bool,
+    /// Keep scrolled to the bottom as the preview grows, like `tail -f`
+    #[builder(setter(into, strip_option), default = "false")]
+    follow: bool,
+    /// Cycle when scrolling past the start or end of the preview
+    #[builder(setter(into, strip_option), default = "false")]
+    cycle: bool,
+    /// Hide the preview window until it is toggled
+    #[builder(setter(into, strip_option), default = "false")]
+    hidden: bool,
+    /// The expression for the initial scroll offset, such as `+{2}-5` to scroll to the line number
+    /// in the second field, keeping five lines of context above it
+    #[builder(setter(into, strip_option), default = "None")]
+    scroll: Option<String>,
+    /// The number of lines at the top of the preview to keep as a fixed header
+    #[builder(setter(into, strip_option), default = "None")]
+    header_lines: Option<u16>,
+    /// The layout to use instead when the preview window would be smaller than the threshold
+    #[builder(setter(custom), default = "None")]
+    alternative: Option<(u16, Box<PreviewWindow>)>,
+}
+
+impl PreviewWindowBuilder {
+    /// The layout to use instead when the preview window would be smaller than `threshold` lines,
+    /// or columns when it is on the left or right, such as `<80(down)`
+    ///
+    /// The alternative layout's own alternative is ignored, as `fzf` doesn't support nesting them
+    pub fn alternative(&mut self, threshold: u16, layout: PreviewWindow) -> &mut Self {
+        self.alternative = Some(Some((threshold, Box::new(layout))));
+        self
+    }
+
+    /// Checks that the size is no more than the space available
+    fn validate(&self) -> Result<(), FzfError> {
+        match self.size {
+            Some(Some(size)) => size.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl PreviewWindow {
+    /// Creates a [`PreviewWindowBuilder`]
+    pub fn builder() -> PreviewWindowBuilder {
+        PreviewWindowBuilder::default()
+    }
+
+    /// Creates the comma separated list of options, without the alternative layout
+    fn options(&self) -> Vec<String> {
+        let mut options = vec![];
+
+        if let Some(position) = self.position {
+            options.push(position.to_string());
+        }
+        if let Some(size) = self.size {
+            options.push(size.to_string());
+        }
+        match self.border {
+            Some(Border::None) => options.push("noborder".to_string()),
+            Some(border) => options.push(format!("border-{}", border)),
+            None => (),
+        }
+        for (option, value) in [
+            ("wrap", self.wrap),
+            ("follow", self.follow),
+            ("cycle", self.cycle),
+            ("hidden", self.hidden),
+        ] {
+            if value {
+                options.push(option.to_string());
+            }
+        }
+        if let Some(scroll) = &self.scroll {
+            match scroll.starts_with('+') {
+                true => options.push(scroll.clone()),
+                false => options.push(format!("+{}", scroll)),
+            }
+        }
+        if let Some(lines) = self.header_lines {
+            options.push(format!("~{}", lines));
+        }
+
+        options
+    }
+}
+
+impl fmt::Display for PreviewWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut options = self.options();
+        if let Some((threshold, layout)) = &self.alternative {
+            options.push(format!("<{}({})", threshold, layout.options().join(",")));
+        }
+        write!(f, "{}", options.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preview_window_is_rendered_for_fzf() {
+        let down = PreviewWindow::builder()
+            .position(PreviewPosition::Down)
+            .size(Size::Lines(10))
+            .build()
+            .unwrap();
+        let window = PreviewWindow::builder()
+            .size(Size::Percent(60))
+            .border(Border::Rounded)
+            .wrap(true)
+            .scroll("{2}-5")
+            .header_lines(3u16)
+            .alternative(80, down)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            window.to_string(),
+            "60%,border-rounded,wrap,+{2}-5,~3,<80(down,10)"
+        );
+        assert_eq!(PreviewWindow::default().to_string(), "");
+        assert!(PreviewWindow::builder()
+            .size(Size::Percent(101))
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,737 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStderr, ChildStdin},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+};
+
+use crate::callback::{self, CallbackServer};
+use crate::error::FzfError;
+use crate::feeder::{Feeder, ItemSender};
+use crate::item;
+use crate::key::Key;
+#[cfg(feature = "listen")]
+use crate::listen::FzfClient;
+use crate::options::{Delimiter, MultiOrder};
+use crate::outcome::{Accepted, FzfExit, FzfOutcome, Selection, EXIT_NO_MATCH};
+
+/// The number of bytes of `fzf`'s stderr that are kept for [`FzfOutcome::Failed`]
+const STDERR_CAPTURE_LIMIT: usize = 8 * 1024;
+
+#[derive(Debug)]
+/// Struct that represents a running instance of `fzf`, created by [`crate::Fzf::run`]
+pub struct FzfSession {
+    /// Contains the child stuct returned when the fzf command is spawned
+    instance: Child,
+    /// Contains the child process stdin handle, until it is closed
+    stdin: Option<ChildStdin>,
+    /// Contains the thread forwarding the child process stderr, which returns the captured stderr
+    /// once `fzf` exits
+    stderr: Option<JoinHandle<String>>,
+    /// Contains the background thread feeding items to `fzf`, once `stdin` has been moved to it
+    feeder: Option<Feeder>,
+    /// Warnings about options that weren't passed to `fzf`, because of the version policy
+    warnings: Vec<String>,
+    /// The server answering callbacks from the commands `fzf` runs, which is stopped once `fzf`
+    /// exits
+    callbacks: Option<CallbackServer>,
+    /// The client for the http server `fzf` starts with the `listen` option
+    #[cfg(feature = "listen")]
+    client: Option<FzfClient>,
+    /// Keeps track of the items added, for parsing `fzf`'s output
+    tracker: Arc<Mutex<ItemTracker>>,
+}
+
+impl FzfSession {
+    /// Creates a session for the given `fzf` child process
+    pub(crate) fn new(
+        mut instance: Child,
+        format: OutputFormat,
+        warnings: Vec<String>,
+        callbacks: Option<CallbackServer>,
+    ) -> Self {
+        Self {
+            stdin: instance.stdin.take(),
+            stderr: instance.stderr.take().map(forward_stderr),
+            instance,
+            warnings,
+            callbacks,
+            #[cfg(feature = "listen")]
+            client: None,
+            feeder: None,
+            tracker: Arc::new(Mutex::new(ItemTracker::new(format))),
+        }
+    }
+
+    /// Locks the item tracker, which is shared with the feeder thread
+    fn tracker(&self) -> MutexGuard<'_, ItemTracker> {
+        self.tracker.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Gets the feeder thread, moving `stdin` onto it if it hasn't been started yet
+    fn feeder(&mut self) -> Result<&Feeder, FzfError> {
+        if let Some(stdin) = self.stdin.take() {
+            self.feeder = Some(Feeder::spawn(stdin, Arc::clone(&self.tracker)));
+        }
+        self.feeder.as_ref().ok_or(FzfError::StdinClosed)
+    }
+
+    /// Gets the stdin handle of `fzf`, for writing items to it directly
+    ///
+    /// Each line written is displayed as an item in `fzf`. Items written this way are not
+    /// tracked by the session, so they are not sorted when using [`MultiOrder::Input`]
+    ///
+    /// # Returns
+    ///
+    /// The stdin handle, or `None` if it has already been closed or moved to the feeder thread
+    pub fn stdin(&mut self) -> Option<&mut ChildStdin> {
+        self.stdin.as_mut()
+    }
+
+    /// Closes `fzf`'s stdin, letting it know that no more items will be added
+    ///
+    /// If items are being fed from a background thread, stdin is closed once every
+    /// [`ItemSender`] has been dropped
+    pub fn close_stdin(&mut self) {
+        drop(self.stdin.take());
+        if let Some(feeder) = self.feeder.as_mut() {
+            feeder.close();
+        }
+    }
+
+    /// Feeds every item from the given iterator to `fzf` from a background thread
+    ///
+    /// Unlike `add_items`, this returns immediately, so `output` can be called while the items
+    /// are still being produced. Once the user has made their selection the producer is stopped
+    /// before its next item, which makes this suitable for slow or endless iterators
+    ///
+    /// # Parameters
+    ///
+    /// - `items` The items to add
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::StdinClosed`] if stdin has already been closed
+    pub fn feed<I>(&mut self, items: I) -> Result<(), FzfError>
+    where
+        I: IntoIterator + Send + 'static,
+        I::Item: Into<String>,
+    {
+        self.feeder()?.feed(items)
+    }
+
+    /// Creates an [`ItemSender`], which can be cloned and moved to other threads to add items to
+    /// `fzf` while the session waits for output
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::StdinClosed`] if stdin has already been closed
+    pub fn sender(&mut self) -> Result<ItemSender, FzfError> {
+        self.feeder()?.sender().ok_or(FzfError::StdinClosed)
+    }
+
+    /// Adds an item to the `fzf` selection ui
+    ///
+    /// Once `feed` or `sender` has moved stdin to the feeder thread, the item is queued for that
+    /// thread to write instead, so returning `Ok` doesn't mean `fzf` has read it yet
+    ///
+    /// # Parameters
+    ///
+    /// - `item` The item to add
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::StdinClosed`] if stdin has already been closed, or
+    /// [`FzfError::BrokenPipe`] if `fzf` has stopped reading items
+    pub fn add_item<T: Into<String>>(&mut self, item: T) -> Result<(), FzfError> {
+        if self.stdin.is_none() {
+            // Items have to go through the feeder thread once it owns stdin, to keep them in order
+            let sender = self.feeder.as_ref().and_then(Feeder::sender);
+            return sender.ok_or(FzfError::StdinClosed)?.send(item);
+        }
+        let line = self.tracker().line(item.into());
+        let stdin = self.stdin.as_mut().ok_or(FzfError::StdinClosed)?;
+        stdin.write_all(line.as_bytes()).map_err(FzfError::write)?;
+        self.tracker().sent();
+        Ok(())
+    }
+
+    /// Adds all the items in the given vec to the `fzf` selection ui
+    ///
+    /// Essentially a wrapper for `add_item` for convenience. If `fzf` stops reading items part way
+    /// through, which happens when the user makes their selection before every item was added, the
+    /// remaining items are skipped rather than treated as an error, so that the selection can
+    /// still be fetched with `output`
+    ///
+    /// # Parameters
+    ///
+    /// - `items` The items to add
+    ///
+    /// # Returns
+    ///
+    /// The number of items that were added before `fzf` stopped reading them. Once stdin has been
+    /// moved to the feeder thread this counts the items queued for it, some of which may never be
+    /// written, so use `items_sent` for the number `fzf` was actually given
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `add_item`, other than [`FzfError::BrokenPipe`]
+    pub fn add_items(
+        &mut self,
+        items: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<usize, FzfError> {
+        let mut added = 0;
+        for item in items.into_iter() {
+            match self.add_item(item) {
+                Ok(()) => added += 1,
+                Err(FzfError::BrokenPipe) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(added)
+    }
+
+    /// Gets the warnings about options that weren't passed to `fzf`, because the installed version
+    /// doesn't support them
+    ///
+    /// Only options dropped by [`crate::VersionPolicy::Warn`] are reported
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Sets the client for the http server `fzf` starts with the `listen` option
+    #[cfg(feature = "listen")]
+    pub(crate) fn with_client(mut self, client: Option<FzfClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Gets a client for the http server `fzf` starts with the `listen` option, which can send
+    /// actions to `fzf` and read its state while the user is making their selection
+    ///
+    /// # Returns
+    ///
+    /// `None` if the `listen` option wasn't set
+    #[cfg(feature = "listen")]
+    pub fn client(&self) -> Option<FzfClient> {
+        self.client.clone()
+    }
+
+    /// Gets the number of items that have been sent to `fzf` so far, including those sent from
+    /// the feeder thread
+    pub fn items_sent(&self) -> usize {
+        self.tracker().items_sent()
+    }
+
+    /// Waits for `fzf` to exit, returning its exit status, stdout, captured stderr and whether it
+    /// showed its ui
+    fn wait(&mut self) -> Result<FzfExit, FzfError> {
+        self.close_stdin();
+        let mut stdout = vec![];
+        if let Some(mut handle) = self.instance.stdout.take() {
+            handle.read_to_end(&mut stdout).map_err(FzfError::Wait)?;
+        }
+        let status = self.instance.wait().map_err(FzfError::Wait)?;
+        drop(self.callbacks.take());
+        if let Some(feeder) = &self.feeder {
+            feeder.stop();
+        }
+        let stderr = self
+            .stderr
+            .take()
+            .and_then(|x| x.join().ok())
+            .unwrap_or_default();
+        Ok(FzfExit {
+            status,
+            stdout,
+            stderr,
+            interacted: self.tracker().interacted(),
+        })
+    }
+
+    /// Gets the output of `fzf`.
+    ///
+    /// **NOTE: Blocks execution until output is received**
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing the users selection as a [`String`] if they selected one
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::Wait`] if waiting for `fzf` failed, [`FzfError::ExitCode`] if `fzf`
+    /// exited with an undocumented exit code, or [`FzfError::InvalidUtf8`] if the output of `fzf`
+    /// was not valid UTF-8
+    pub fn output(mut self) -> Result<FzfOutcome<String>, FzfError> {
+        let exit = self.wait()?;
+        FzfOutcome::from_exit(exit, |stdout| self.tracker().selection(stdout))
+    }
+
+    /// Gets the output of `fzf` when multi-select is enabled, with each selected item as its own
+    /// entry.
+    ///
+    /// The items are returned in the order specified by the `multi_order` option
+    ///
+    /// **NOTE: Blocks execution until output is received**
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing the users selections as a [`Vec`] of [`String`]'s if they
+    /// selected any
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `output` method
+    pub fn output_multi(mut self) -> Result<FzfOutcome<Vec<String>>, FzfError> {
+        let exit = self.wait()?;
+        FzfOutcome::from_exit(exit, |stdout| self.tracker().selections(stdout))
+    }
+
+    /// Gets the matches printed by `fzf` when running in filter mode
+    ///
+    /// `fzf` exits with code 1 when nothing matched, which is returned as an empty [`Vec`]
+    pub(crate) fn output_filtered(mut self) -> Result<Vec<String>, FzfError> {
+        let exit = self.wait()?;
+        match exit.status.code() {
+            Some(0) => self.tracker().selections(exit.stdout),
+            Some(EXIT_NO_MATCH) => Ok(vec![]),
+            code => Err(FzfError::ExitCode {
+                code,
+                stderr: exit.stderr,
+            }),
+        }
+    }
+
+    /// Gets the output of `fzf`, with the selected item split into its fields by the `delimiter`
+    /// option, or by whitespace if it wasn't set
+    ///
+    /// When the `accept_nth` option is set, only the fields it picks are returned
+    ///
+    /// **NOTE: Blocks execution until output is received**
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing the fields of the users selection if they selected one
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `output` method
+    pub fn output_fields(mut self) -> Result<FzfOutcome<Vec<String>>, FzfError> {
+        let exit = self.wait()?;
+        FzfOutcome::from_exit(exit, |stdout| {
+            let tracker = self.tracker();
+            Ok(tracker.fields(&tracker.selection(stdout)?))
+        })
+    }
+
+    /// Gets the output of `fzf` when multi-select is enabled, with each selected item split into
+    /// its fields like the `output_fields` method
+    ///
+    /// **NOTE: Blocks execution until output is received**
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing the fields of each of the users selections if they selected
+    /// any
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `output` method
+    pub fn output_fields_multi(mut self) -> Result<FzfOutcome<Vec<Vec<String>>>, FzfError> {
+        let exit = self.wait()?;
+        FzfOutcome::from_exit(exit, |stdout| {
+            let tracker = self.tracker();
+            let selections = tracker.selections(stdout)?;
+            Ok(selections.iter().map(|x| tracker.fields(x)).collect())
+        })
+    }
+
+    /// Gets everything `fzf` printed when the user made their selection, including which key
+    /// they accepted with when using the `expect` option
+    ///
+    /// **NOTE: Blocks execution until output is received**
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing a [`Selection`] if the user selected any items
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `output` method
+    pub fn output_selection(mut self) -> Result<FzfOutcome<Selection>, FzfError> {
+        let exit = self.wait()?;
+        FzfOutcome::from_exit(exit, |stdout| self.tracker().parse(stdout))
+    }
+
+    /// Gets whether the user accepted an item, or their query because nothing matched it
+    ///
+    /// This is meant to be used with the `accept_query` option, which lets the user press enter
+    /// to accept their query when nothing matches it
+    ///
+    /// **NOTE: Blocks execution until output is received**
+    ///
+    /// # Returns
+    ///
+    /// An [`FzfOutcome`] containing what the user [`Accepted`]
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as the `output` method
+    pub fn output_accepted(self) -> Result<FzfOutcome<Accepted>, FzfError> {
+        Ok(self.output_selection()?.map(Selection::accepted))
+    }
+}
+
+#[derive(Debug, Default)]
+/// Describes the output `fzf` has been configured to print, so that it can be parsed
+pub(crate) struct OutputFormat {
+    /// Whether each item is prefixed with a hidden index field, used for selecting typed items
+    pub(crate) indexed: bool,
+    /// The order multiple selections are returned in
+    pub(crate) multi_order: MultiOrder,
+    /// The file `fzf` creates once it shows its ui, if it may skip it because of `--select-1` or
+    /// `--exit-0`
+    pub(crate) start_marker: Option<StartMarker>,
+    /// Whether `--print-query` was passed, which prints the query before everything else
+    pub(crate) print_query: bool,
+    /// The keys passed to `--expect`, one of which is printed before the selection
+    pub(crate) expect: Vec<Key>,
+    /// What separates the fields of each item, or `None` if they're separated by whitespace
+    pub(crate) delimiter: Option<Delimiter>,
+}
+
+impl OutputFormat {
+    /// Splits a line printed by `fzf` into its fields, without its index field
+    ///
+    /// Like `fzf`, a delimiter at the end of the line ends the last field rather than starting an
+    /// empty one
+    pub(crate) fn fields(&self, line: &str) -> Vec<String> {
+        let line = match self.indexed {
+            true => line.split_once('\t').map_or(line, |(_, text)| text),
+            false => line,
+        };
+        let mut fields = match &self.delimiter {
+            Some(delimiter) => delimiter.split(line),
+            None => line.split_whitespace().map(String::from).collect(),
+        };
+        if fields.len() > 1 && fields.last().is_some_and(String::is_empty) {
+            fields.pop();
+        }
+        fields
+    }
+}
+
+#[derive(Debug)]
+/// The file `fzf` creates once it shows its ui, in a directory only the current user can write to
+///
+/// Both are removed when the marker is dropped
+pub(crate) struct StartMarker {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl StartMarker {
+    /// Creates the directory for the marker, with a name other users can't guess
+    ///
+    /// The file is created with `touch`, which needs a POSIX shell, so there's no marker on other
+    /// platforms and the ui is always assumed to have been shown
+    #[cfg(unix)]
+    pub(crate) fn create() -> io::Result<Option<Self>> {
+        use std::os::unix::fs::DirBuilderExt;
+
+        let dir = env::temp_dir().join(format!("fzf-wrapped-{}", callback::new_token()));
+        // Fails if the directory already exists, so nobody else can have made it first
+        fs::DirBuilder::new().mode(0o700).create(&dir)?;
+        let path = dir.join("started");
+        Ok(Some(Self { dir, path }))
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn create() -> io::Result<Option<Self>> {
+        Ok(None)
+    }
+
+    /// Gets the path of the file `fzf` creates
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Checks whether `fzf` created the file
+    pub(crate) fn exists(&self) -> bool {
+        self.path.exists()
+    }
+}
+
+impl Drop for StartMarker {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
+
+#[derive(Debug)]
+/// Keeps track of the items added to `fzf`, so that its output can be parsed
+pub(crate) struct ItemTracker {
+    /// The position each item was first added at, used to sort multiple selections into input
+    /// order
+    positions: HashMap<String, usize>,
+    /// The number of items successfully sent to `fzf` so far
+    item_count: usize,
+    /// The output `fzf` has been configured to print
+    format: OutputFormat,
+}
+
+impl ItemTracker {
+    pub(crate) fn new(format: OutputFormat) -> Self {
+        Self {
+            positions: HashMap::new(),
+            item_count: 0,
+            format,
+        }
+    }
+
+    /// Records the given item, returning the line to write to `fzf` for it
+    pub(crate) fn line(&mut self, item: String) -> String {
+        // Trimming the string to make sure we don't double up on newline characters
+        let mut item = item.trim().to_string();
+        if self.format.multi_order == MultiOrder::Input && !self.format.indexed {
+            self.positions
+                .entry(item.clone())
+                .or_insert(self.item_count);
+        }
+        item.push('\n');
+        item
+    }
+
+    /// Records that the last item was successfully sent to `fzf`
+    pub(crate) fn sent(&mut self) {
+        self.item_count += 1;
+    }
+
+    /// Gets the number of items successfully sent to `fzf`
+    pub(crate) fn items_sent(&self) -> usize {
+        self.item_count
+    }
+
+    /// Checks whether `fzf` showed its ui
+    pub(crate) fn interacted(&self) -> bool {
+        match &self.format.start_marker {
+            Some(marker) => marker.exists(),
+            None => true,
+        }
+    }
+
+    /// Parses the stdout of `fzf`, sorting the items by the `multi_order` option
+    pub(crate) fn parse(&self, stdout: Vec<u8>) -> Result<Selection, FzfError> {
+        let stdout = String::from_utf8(stdout).map_err(FzfError::InvalidUtf8)?;
+        let mut lines = stdout.lines();
+
+        let mut selection = Selection::default();
+        if self.format.print_query {
+            selection.query = Some(lines.next().unwrap_or_default().to_string());
+        }
+        if !self.format.expect.is_empty() {
+            // `fzf` prints the key exactly as it was given to `--expect`, or an empty line if the
+            // user accepted with any other key
+            let line = lines.next().unwrap_or_default();
+            selection.key = self
+                .format
+                .expect
+                .iter()
+                .find(|x| x.to_string() == line)
+                .copied();
+        }
+        selection.items = lines.map(|x| x.to_string()).collect();
+
+        match self.format.multi_order {
+            MultiOrder::Picked => (),
+            MultiOrder::Input if self.format.indexed => selection
+                .items
+                .sort_by_key(|x| item::parse_index(x).unwrap_or(usize::MAX)),
+            MultiOrder::Input => selection
+                .items
+                .sort_by_key(|x| self.positions.get(x).copied().unwrap_or(usize::MAX)),
+        }
+        Ok(selection)
+    }
+
+    /// Parses the stdout of `fzf` into a single selection
+    pub(crate) fn selection(&self, stdout: Vec<u8>) -> Result<String, FzfError> {
+        Ok(self
+            .parse(stdout)?
+            .items
+            .into_iter()
+            .next()
+            .unwrap_or_default())
+    }
+
+    /// Parses the stdout of `fzf` into multiple selections
+    pub(crate) fn selections(&self, stdout: Vec<u8>) -> Result<Vec<String>, FzfError> {
+        Ok(self.parse(stdout)?.items)
+    }
+
+    /// Splits a selected line into its fields
+    pub(crate) fn fields(&self, line: &str) -> Vec<String> {
+        self.format.fields(line)
+    }
+}
+
+#[derive(Debug, Default)]
+/// Keeps the tail end of `fzf`'s stderr for error reporting
+pub(crate) struct StderrCapture {
+    captured: Vec<u8>,
+}
+
+impl StderrCapture {
+    /// Forwards the given bytes to our own stderr, and captures them
+    pub(crate) fn forward(&mut self, bytes: &[u8]) {
+        let _ = io::stderr().write_all(bytes);
+        self.capture(bytes);
+    }
+
+    /// Captures the given bytes, without forwarding them
+    pub(crate) fn capture(&mut self, bytes: &[u8]) {
+        self.captured.extend_from_slice(bytes);
+        if self.captured.len() > STDERR_CAPTURE_LIMIT {
+            self.captured
+                .drain(..self.captured.len() - STDERR_CAPTURE_LIMIT);
+        }
+    }
+
+    /// Gets everything that was captured
+    pub(crate) fn finish(self) -> String {
+        String::from_utf8_lossy(&self.captured).trim().to_string()
+    }
+}
+
+/// Spawns a thread that forwards `fzf`'s stderr to our own, so that the ui is still displayed when
+/// `fzf` draws to stderr, while keeping the tail end of it for error reporting
+fn forward_stderr(mut stderr: ChildStderr) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut capture = StderrCapture::default();
+        let mut buffer = [0; 4096];
+        while let Ok(read @ 1..) = stderr.read(&mut buffer) {
+            capture.forward(&buffer[..read]);
+        }
+        capture.finish()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn adding_items_stops_once_fzf_closes_stdin() {
+        let stand_in = crate::executable::stand_in("first-line", "IFS= read -r x; echo \"$x\"");
+        let fzf = crate::Fzf::builder()
+            .executable(stand_in.path())
+            .build()
+            .unwrap();
+        let mut session = fzf.run().unwrap();
+
+        // More than fits in the pipe's buffer, so the writes fail once the stand-in has exited
+        let items: Vec<String> = (0..100_000).map(|x| format!("item {}", x)).collect();
+        let added = session.add_items(items).unwrap();
+        assert!(added < 100_000);
+        assert_eq!(added, session.items_sent());
+        assert_eq!(
+            session.output().unwrap(),
+            FzfOutcome::Selected("item 0".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn start_marker_is_private_and_cleaned_up() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let marker = StartMarker::create().unwrap().unwrap();
+        let dir = marker.path().parent().unwrap().to_path_buf();
+        assert_eq!(
+            fs::metadata(&dir).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+        assert!(!marker.exists());
+
+        fs::write(marker.path(), "").unwrap();
+        assert!(marker.exists());
+        assert_ne!(
+            StartMarker::create().unwrap().unwrap().path(),
+            marker.path()
+        );
+
+        drop(marker);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn expected_key_is_split_from_the_items() {
+        let tracker = ItemTracker::new(OutputFormat {
+            expect: vec![Key::Ctrl('v'), Key::Ctrl('d')],
+            ..Default::default()
+        });
+
+        let selection = tracker.parse(b"ctrl-d\nred\nblue\n".to_vec()).unwrap();
+        assert_eq!(selection.key, Some(Key::Ctrl('d')));
+        assert_eq!(selection.items, vec!["red", "blue"]);
+
+        let selection = tracker.parse(b"\nred\n".to_vec()).unwrap();
+        assert_eq!(selection.key, None);
+        assert_eq!(selection.items, vec!["red"]);
+    }
+
+    #[test]
+    fn query_is_accepted_when_nothing_matched() {
+        let tracker = ItemTracker::new(OutputFormat {
+            print_query: true,
+            expect: vec![Key::Ctrl('v')],
+            ..Default::default()
+        });
+
+        let selection = tracker.parse(b"re\n\nred\n".to_vec()).unwrap();
+        assert_eq!(selection.query.as_deref(), Some("re"));
+        assert_eq!(selection.accepted(), Accepted::Item("red".to_string()));
+
+        // `accept-or-print-query` only prints the query
+        let selection = tracker.parse(b"purple\n".to_vec()).unwrap();
+        assert_eq!(selection.accepted(), Accepted::Query("purple".to_string()));
+    }
+
+    #[test]
+    fn selections_are_split_into_fields() {
+        let format = |indexed, delimiter: Option<&str>| OutputFormat {
+            indexed,
+            delimiter: delimiter.map(Delimiter::from),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format(false, None).fields("  42  red\t/tmp "),
+            vec!["42", "red", "/tmp"]
+        );
+        assert_eq!(
+            format(false, Some("\t")).fields("42\tred\t"),
+            vec!["42", "red"]
+        );
+        assert_eq!(format(false, Some(",")).fields(",red,"), vec!["", "red"]);
+        assert_eq!(
+            format(true, Some(",")).fields("3\t42,red"),
+            vec!["42", "red"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn selections_are_split_by_regex() {
+        let regex = OutputFormat {
+            delimiter: Some(Delimiter::regex("[:/]").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(regex.fields("a:b/c"), vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,296 @@
+//! This module contains the [`ThemePreset`] enum, and the loading of [`ColorScheme`]s from theme
+//! files with the `themes` feature, so that every tool built on [`crate::Fzf`] can share one theme
+//!
+//! A theme file is a small TOML or JSON document, with an optional preset to start from, an
+//! optional base theme, and the colors of each part of the ui written the way `fzf` takes them:
+//!
+//! ```toml
+//! preset = "solarized-dark"
+//! base = "dark"
+//!
+//! [colors]
+//! hl = "bright-yellow"
+//! "bg+" = "#282c34"
+//! prompt = "39:bold"
+//! ```
+
+#[cfg(feature = "themes")]
+use std::{collections::BTreeMap, fs, path::Path};
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "themes")]
+use serde::Deserialize;
+
+use crate::color::{
+    AnsiColor, ColorAttr, ColorElement, ColorScheme, ColorSchemeBuilder, ColorSpec,
+};
+use crate::error::FzfError;
+use crate::options::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Enum to represent the color schemes that come with this crate
+pub enum ThemePreset {
+    /// Bright text on black, with underlined matches
+    HighContrast,
+    /// The dark variant of the Solarized palette
+    SolarizedDark,
+    /// The light variant of the Solarized palette
+    SolarizedLight,
+    /// No colors, with matches and the current line picked out by attributes alone
+    Monochrome,
+}
+
+impl ThemePreset {
+    /// Every preset
+    const ALL: [Self; 4] = [
+        Self::HighContrast,
+        Self::SolarizedDark,
+        Self::SolarizedLight,
+        Self::Monochrome,
+    ];
+
+    /// Creates a [`ColorSchemeBuilder`] with the preset's colors, which can be changed further
+    pub fn builder(self) -> ColorSchemeBuilder {
+        use AnsiColor::*;
+        use ColorAttr::*;
+        use ColorElement::*;
+
+        let mut builder = ColorScheme::builder();
+        match self {
+            Self::HighContrast => builder
+                .base(Color::Dark)
+                .element(Fg, BrightWhite)
+                .element(Bg, Black)
+                .element(Hl, ColorSpec::new(BrightYellow).attr(Bold).attr(Underline))
+                .element(CurrentFg, ColorSpec::new(Black).attr(Bold))
+                .element(CurrentBg, BrightWhite)
+                .element(CurrentHl, ColorSpec::new(Red).attr(Bold).attr(Underline))
+                .element(Info, BrightCyan)
+                .element(Prompt, ColorSpec::new(BrightCyan).attr(Bold))
+                .element(Pointer, ColorSpec::new(BrightRed).attr(Bold))
+                .element(Marker, ColorSpec::new(BrightGreen).attr(Bold))
+                .element(Spinner, BrightCyan)
+                .element(Header, ColorSpec::new(BrightWhite).attr(Bold))
+                .element(Border, BrightWhite),
+            Self::SolarizedDark => builder
+                .base(Color::Dark)
+                .element(Fg, solarized::BASE0)
+                .element(Bg, solarized::BASE03)
+                .element(Hl, solarized::BLUE)
+                .element(CurrentFg, solarized::BASE1)
+                .element(CurrentBg, solarized::BASE02)
+                .element(CurrentHl, solarized::BLUE)
+                .element(Info, solarized::YELLOW)
+                .element(Prompt, solarized::CYAN)
+                .element(Pointer, solarized::ORANGE)
+                .element(Marker, solarized::GREEN)
+                .element(Spinner, solarized::YELLOW)
+                .element(Header, solarized::CYAN)
+                .element(Border, solarized::BASE01),
+            Self::SolarizedLight => builder
+                .base(Color::Light)
+                .element(Fg, solarized::BASE00)
+                .element(Bg, solarized::BASE3)
+                .element(Hl, solarized::BLUE)
+                .element(CurrentFg, solarized::BASE01)
+                .element(CurrentBg, solarized::BASE2)
+                .element(CurrentHl, solarized::BLUE)
+                .element(Info, solarized::YELLOW)
+                .element(Prompt, solarized::CYAN)
+                .element(Pointer, solarized::ORANGE)
+                .element(Marker, solarized::GREEN)
+                .element(Spinner, solarized::YELLOW)
+                .element(Header, solarized::CYAN)
+                .element(Border, solarized::BASE1),
+            Self::Monochrome => builder
+                .base(Color::Bw)
+                .element(Hl, Underline)
+                .element(CurrentFg, Reverse)
+                .element(CurrentHl, ColorSpec::from(Reverse).attr(Underline))
+                .element(Pointer, Bold)
+                .element(Marker, Bold)
+                .element(Header, Italic),
+        };
+        builder
+    }
+}
+
+impl fmt::Display for ThemePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::HighContrast => "high-contrast",
+            Self::SolarizedDark => "solarized-dark",
+            Self::SolarizedLight => "solarized-light",
+            Self::Monochrome => "monochrome",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl FromStr for ThemePreset {
+    type Err = FzfError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|x| x.to_string() == name)
+            .ok_or_else(|| FzfError::InvalidTheme(format!("unknown preset {}", s)))
+    }
+}
+
+impl From<ThemePreset> for ColorScheme {
+    fn from(value: ThemePreset) -> Self {
+        value
+            .builder()
+            .build()
+            .expect("presets only set fields with defaults")
+    }
+}
+
+/// The colors of the Solarized palette used by the presets
+mod solarized {
+    use crate::color::ColorValue;
+
+    pub(super) const BASE03: ColorValue = ColorValue::Rgb(0x00, 0x2b, 0x36);
+    pub(super) const BASE02: ColorValue = ColorValue::Rgb(0x07, 0x36, 0x42);
+    pub(super) const BASE01: ColorValue = ColorValue::Rgb(0x58, 0x6e, 0x75);
+    pub(super) const BASE00: ColorValue = ColorValue::Rgb(0x65, 0x7b, 0x83);
+    pub(super) const BASE0: ColorValue = ColorValue::Rgb(0x83, 0x94, 0x96);
+    pub(super) const BASE1: ColorValue = ColorValue::Rgb(0x93, 0xa1, 0xa1);
+    pub(super) const BASE2: ColorValue = ColorValue::Rgb(0xee, 0xe8, 0xd5);
+    pub(super) const BASE3: ColorValue = ColorValue::Rgb(0xfd, 0xf6, 0xe3);
+    pub(super) const YELLOW: ColorValue = ColorValue::Rgb(0xb5, 0x89, 0x00);
+    pub(super) const ORANGE: ColorValue = ColorValue::Rgb(0xcb, 0x4b, 0x16);
+    pub(super) const CYAN: ColorValue = ColorValue::Rgb(0x2a, 0xa1, 0x98);
+    pub(super) const BLUE: ColorValue = ColorValue::Rgb(0x26, 0x8b, 0xd2);
+    pub(super) const GREEN: ColorValue = ColorValue::Rgb(0x85, 0x99, 0x00);
+}
+
+#[cfg(feature = "themes")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// The contents of a theme file
+struct ThemeFile {
+    preset: Option<String>,
+    base: Option<String>,
+    #[serde(default)]
+    colors: BTreeMap<String, String>,
+}
+
+#[cfg(feature = "themes")]
+impl ThemeFile {
+    /// Creates the color scheme the file describes
+    fn into_scheme(self) -> Result<ColorScheme, FzfError> {
+        let mut builder = match &self.preset {
+            Some(preset) => preset.parse::<ThemePreset>()?.builder(),
+            None => ColorScheme::builder(),
+        };
+        if let Some(base) = &self.base {
+            builder.base(parse_base(base)?);
+        }
+        for (element, spec) in &self.colors {
+            builder.element(element.parse()?, spec.parse::<ColorSpec>()?);
+        }
+        builder.build()
+    }
+}
+
+/// Parses the name of a base theme, which unlike `Color::from` rejects unknown names
+#[cfg(feature = "themes")]
+fn parse_base(name: &str) -> Result<Color, FzfError> {
+    match name.to_lowercase().as_str() {
+        "dark" => Ok(Color::Dark),
+        "light" => Ok(Color::Light),
+        "16" => Ok(Color::Sixteen),
+        "bw" => Ok(Color::Bw),
+        _ => Err(FzfError::InvalidTheme(format!(
+            "unknown base theme {}",
+            name
+        ))),
+    }
+}
+
+#[cfg(feature = "themes")]
+impl ColorScheme {
+    /// Parses a theme written in TOML
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::InvalidTheme`] if the theme is not valid
+    pub fn from_toml(theme: &str) -> Result<Self, FzfError> {
+        toml::from_str::<ThemeFile>(theme)
+            .map_err(|e| FzfError::InvalidTheme(e.to_string()))?
+            .into_scheme()
+    }
+
+    /// Parses a theme written in JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::InvalidTheme`] if the theme is not valid
+    pub fn from_json(theme: &str) -> Result<Self, FzfError> {
+        serde_json::from_str::<ThemeFile>(theme)
+            .map_err(|e| FzfError::InvalidTheme(e.to_string()))?
+            .into_scheme()
+    }
+
+    /// Loads a theme file, which is read as JSON if it ends in `.json`, and TOML otherwise
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FzfError::InvalidTheme`] if the file could not be read, or the theme is not valid
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FzfError> {
+        let path = path.as_ref();
+        let theme = fs::read_to_string(path).map_err(|e| {
+            FzfError::InvalidTheme(format!("failed to read {}: {}", path.display(), e))
+        })?;
+        match path.extension().is_some_and(|x| x == "json") {
+            true => Self::from_json(&theme),
+            false => Self::from_toml(&theme),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "themes")]
+    fn theme_files_are_layered_on_presets() {
+        let toml = r##"
+            preset = "monochrome"
+            base = "light"
+
+            [colors]
+            hl = "#ff8700:bold"
+        "##;
+        let json = r#"{ "preset": "monochrome", "base": "light", "colors": { "hl": "208:bold" } }"#;
+
+        let scheme = ColorScheme::from_toml(toml).unwrap().to_string();
+        assert!(scheme.starts_with("light,"));
+        assert!(scheme.ends_with(",hl:#ff8700:bold"));
+        assert!(!scheme.contains("hl:underline"));
+
+        let scheme = ColorScheme::from_json(json).unwrap().to_string();
+        assert!(scheme.ends_with(",hl:208:bold"));
+
+        assert!(ColorScheme::from_toml("base = \"dim\"").is_err());
+        assert!(ColorScheme::from_toml("[colors]\nfg = \"mauve\"").is_err());
+        assert!(ColorScheme::from_json("{ \"colours\": {} }").is_err());
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        for preset in ThemePreset::ALL {
+            let scheme = ColorScheme::from(preset).to_string();
+            assert_eq!(preset.to_string().parse::<ThemePreset>().ok(), Some(preset));
+            assert!(
+                scheme.split(',').skip(1).all(|x| x.contains(':')),
+                "{}",
+                scheme
+            );
+        }
+    }
+}
@@ -0,0 +1,456 @@
+//! This module contains the [`FzfVersion`] struct, and the checks that stop options being passed
+//! to versions of `fzf` that don't support them
+
+use std::{
+    fmt, io,
+    path::Path,
+    process::{Command, Output},
+    str::FromStr,
+};
+
+use crate::action;
+use crate::error::FzfError;
+use crate::executable;
+
+/// The version of `fzf` that added the start event, which is used to detect the `select_1` and
+/// `exit_0` options skipping the ui
+pub(crate) const START_EVENT: FzfVersion = FzfVersion::new(0, 35, 0);
+
+/// The minimum version of `fzf` required for each option that may not be supported
+///
+/// Options without an entry have been supported since long before this crate was written. An
+/// entry ending in `~` covers every value starting with it, such as `--height=~40%`
+const OPTION_VERSIONS: &[(&str, FzfVersion)] = &[
+    ("--disabled", FzfVersion::new(0, 25, 0)),
+    ("--keep-right", FzfVersion::new(0, 25, 0)),
+    ("--header-first", FzfVersion::new(0, 28, 0)),
+    ("--border=bold", FzfVersion::new(0, 29, 0)),
+    ("--border=double", FzfVersion::new(0, 29, 0)),
+    ("--scheme", FzfVersion::new(0, 32, 0)),
+    ("--height=~", FzfVersion::new(0, 34, 0)),
+    ("--border=block", FzfVersion::new(0, 35, 0)),
+    ("--border=thinblock", FzfVersion::new(0, 35, 0)),
+    ("--border-label", FzfVersion::new(0, 35, 0)),
+    ("--border-label-pos", FzfVersion::new(0, 35, 0)),
+    ("--preview-label", FzfVersion::new(0, 35, 0)),
+    ("--preview-label-pos", FzfVersion::new(0, 35, 0)),
+    ("--separator", FzfVersion::new(0, 35, 0)),
+    ("--no-separator", FzfVersion::new(0, 35, 0)),
+    ("--ellipsis", FzfVersion::new(0, 35, 0)),
+    ("--listen", FzfVersion::new(0, 36, 0)),
+    ("--scroll-off", FzfVersion::new(0, 27, 0)),
+    ("--scrollbar", FzfVersion::new(0, 36, 0)),
+    ("--no-scrollbar", FzfVersion::new(0, 36, 0)),
+    ("--track", FzfVersion::new(0, 39, 0)),
+    ("--info=right", FzfVersion::new(0, 42, 0)),
+    ("--info=inline-right", FzfVersion::new(0, 42, 0)),
+    ("--style", FzfVersion::new(0, 58, 0)),
+    ("--accept-nth", FzfVersion::new(0, 60, 0)),
+    ("--input-border", FzfVersion::new(0, 58, 0)),
+    ("--input-label", FzfVersion::new(0, 58, 0)),
+    ("--input-label-pos", FzfVersion::new(0, 58, 0)),
+    ("--list-border", FzfVersion::new(0, 58, 0)),
+    ("--list-label", FzfVersion::new(0, 58, 0)),
+    ("--list-label-pos", FzfVersion::new(0, 58, 0)),
+    ("--header-border", FzfVersion::new(0, 58, 0)),
+    ("--header-label", FzfVersion::new(0, 58, 0)),
+    ("--header-label-pos", FzfVersion::new(0, 58, 0)),
+];
+
+/// The minimum version of `fzf` required for each value that may not be supported by an option
+/// that takes a list of them
+///
+/// These are the elements of `--color`, the settings of `--preview-window`, the events and actions
+/// of `--bind`, and `*.sock` for a `--listen` unix socket. Events end in `:`, so they aren't
+/// mistaken for actions of the same name
+const VALUE_VERSIONS: &[(&str, &str, FzfVersion)] = &[
+    ("--color", "label", FzfVersion::new(0, 35, 0)),
+    ("--color", "preview-label", FzfVersion::new(0, 35, 0)),
+    ("--color", "separator", FzfVersion::new(0, 35, 0)),
+    ("--color", "scrollbar", FzfVersion::new(0, 36, 0)),
+    ("--color", "preview-border", FzfVersion::new(0, 36, 0)),
+    ("--color", "preview-scrollbar", FzfVersion::new(0, 36, 0)),
+    ("--color", "selected-fg", FzfVersion::new(0, 42, 0)),
+    ("--color", "selected-bg", FzfVersion::new(0, 42, 0)),
+    ("--color", "selected-hl", FzfVersion::new(0, 42, 0)),
+    ("--color", "input-border", FzfVersion::new(0, 58, 0)),
+    ("--color", "input-label", FzfVersion::new(0, 58, 0)),
+    ("--color", "list-border", FzfVersion::new(0, 58, 0)),
+    ("--color", "list-label", FzfVersion::new(0, 58, 0)),
+    ("--color", "header-border", FzfVersion::new(0, 58, 0)),
+    ("--color", "header-label", FzfVersion::new(0, 58, 0)),
+    ("--preview-window", "border-bold", FzfVersion::new(0, 29, 0)),
+    (
+        "--preview-window",
+        "border-double",
+        FzfVersion::new(0, 29, 0),
+    ),
+    (
+        "--preview-window",
+        "border-block",
+        FzfVersion::new(0, 35, 0),
+    ),
+    (
+        "--preview-window",
+        "border-thinblock",
+        FzfVersion::new(0, 35, 0),
+    ),
+    ("--preview-window", "border-line", FzfVersion::new(0, 58, 0)),
+    ("--bind", "start:", START_EVENT),
+    ("--bind", "load:", FzfVersion::new(0, 36, 0)),
+    ("--bind", "focus:", FzfVersion::new(0, 36, 0)),
+    ("--bind", "one:", FzfVersion::new(0, 39, 0)),
+    ("--bind", "zero:", FzfVersion::new(0, 40, 0)),
+    ("--bind", "result:", FzfVersion::new(0, 46, 0)),
+    ("--bind", "resize:", FzfVersion::new(0, 46, 0)),
+    ("--bind", "jump:", FzfVersion::new(0, 52, 0)),
+    ("--bind", "jump-cancel:", FzfVersion::new(0, 52, 0)),
+    ("--bind", "click-header:", FzfVersion::new(0, 52, 0)),
+    ("--bind", "multi:", FzfVersion::new(0, 53, 0)),
+    ("--bind", "rebind", FzfVersion::new(0, 30, 0)),
+    ("--bind", "change-border-label", FzfVersion::new(0, 35, 0)),
+    ("--bind", "change-preview-label", FzfVersion::new(0, 35, 0)),
+    (
+        "--bind",
+        "transform-border-label",
+        FzfVersion::new(0, 35, 0),
+    ),
+    (
+        "--bind",
+        "transform-preview-label",
+        FzfVersion::new(0, 35, 0),
+    ),
+    ("--bind", "change-query", FzfVersion::new(0, 36, 0)),
+    ("--bind", "offset-up", FzfVersion::new(0, 36, 0)),
+    ("--bind", "offset-down", FzfVersion::new(0, 36, 0)),
+    ("--bind", "pos", FzfVersion::new(0, 36, 0)),
+    ("--bind", "reload-sync", FzfVersion::new(0, 36, 0)),
+    ("--bind", "transform-prompt", FzfVersion::new(0, 36, 0)),
+    ("--bind", "transform-query", FzfVersion::new(0, 36, 0)),
+    ("--bind", "become", FzfVersion::new(0, 38, 0)),
+    ("--bind", "toggle-track", FzfVersion::new(0, 39, 0)),
+    ("--bind", "accept-or-print-query", FzfVersion::new(0, 41, 0)),
+    ("--bind", "track-current", FzfVersion::new(0, 41, 0)),
+    ("--bind", "next-selected", FzfVersion::new(0, 42, 0)),
+    ("--bind", "prev-selected", FzfVersion::new(0, 42, 0)),
+    ("--bind", "change-header", FzfVersion::new(0, 45, 0)),
+    ("--bind", "transform", FzfVersion::new(0, 45, 0)),
+    ("--bind", "transform-header", FzfVersion::new(0, 45, 0)),
+    ("--bind", "show-header", FzfVersion::new(0, 50, 0)),
+    ("--bind", "hide-header", FzfVersion::new(0, 50, 0)),
+    ("--bind", "toggle-header", FzfVersion::new(0, 50, 0)),
+    ("--bind", "change-multi", FzfVersion::new(0, 51, 0)),
+    ("--bind", "print", FzfVersion::new(0, 53, 0)),
+    ("--listen", "*.sock", FzfVersion::new(0, 56, 0)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Struct that represents a version of `fzf`, such as `0.40.0`
+pub struct FzfVersion {
+    /// The major version, which has been `0` for every release of `fzf` so far
+    pub major: u32,
+    /// The minor version, which `fzf` increments when adding features
+    pub minor: u32,
+    /// The patch version
+    pub patch: u32,
+}
+
+impl FzfVersion {
+    /// Creates a version from its parts
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for FzfVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for FzfVersion {
+    type Err = FzfError;
+
+    /// Parses the output of `fzf --version`, such as `0.44.1 (d7d2ac3)`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || FzfError::UnknownVersion(s.trim().to_string());
+
+        let version = s.split_whitespace().next().ok_or_else(unknown)?;
+        let mut parts = version.split('.').map(|part| {
+            let digits = part
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(part.len());
+            part[..digits].parse::<u32>().ok()
+        });
+        let mut next = || parts.next().flatten();
+
+        let major = next().ok_or_else(unknown)?;
+        let minor = next().ok_or_else(unknown)?;
+        Ok(Self::new(major, minor, next().unwrap_or(0)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// Enum to represent what happens when an option isn't supported by the installed `fzf`
+pub enum VersionPolicy {
+    /// Pass every option without checking the version of `fzf`
+    #[default]
+    Ignore,
+    /// Drop unsupported options, reporting each of them with [`crate::FzfSession::warnings`]
+    Warn,
+    /// Fail with [`FzfError::UnsupportedOption`]
+    Error,
+}
+
+/// Gets the version of the `fzf` executable found with [`crate::find_fzf`], or the
+/// [`crate::FZF_BIN_ENV`] environment variable
+///
+/// # Errors
+///
+/// Returns [`FzfError::NotInstalled`] if `fzf` could not be found, or
+/// [`FzfError::UnknownVersion`] if its version could not be parsed
+pub fn fzf_version() -> Result<FzfVersion, FzfError> {
+    probe(&executable::resolve(None).ok_or(FzfError::NotInstalled)?)
+}
+
+/// Runs `fzf --version` with the given executable, parsing its output
+pub(crate) fn probe(executable: &Path) -> Result<FzfVersion, FzfError> {
+    parse_output(Command::new(executable).arg("--version").output())
+}
+
+/// Runs `fzf --version` with the given executable using `tokio::process`, so the executor isn't
+/// blocked while it runs
+#[cfg(feature = "tokio")]
+pub(crate) async fn probe_async(executable: &Path) -> Result<FzfVersion, FzfError> {
+    parse_output(
+        tokio::process::Command::new(executable)
+            .arg("--version")
+            .output()
+            .await,
+    )
+}
+
+/// Parses the output of `fzf --version`
+fn parse_output(output: io::Result<Output>) -> Result<FzfVersion, FzfError> {
+    String::from_utf8(output.map_err(FzfError::spawn)?.stdout)
+        .map_err(FzfError::InvalidUtf8)?
+        .parse()
+}
+
+/// Gets the option the given argument sets, and the minimum version of `fzf` that supports it
+fn requirement(arg: &str) -> Option<(&'static str, FzfVersion)> {
+    OPTION_VERSIONS.iter().copied().find(|(option, _)| {
+        arg.strip_prefix(option)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('=') || option.ends_with('~'))
+    })
+}
+
+/// Gets everything in the given argument that may not be supported, with the minimum version of
+/// `fzf` that supports each
+pub(crate) fn requirements(arg: &str) -> Vec<(String, FzfVersion)> {
+    requirement(arg)
+        .map(|(option, required)| (option.to_string(), required))
+        .into_iter()
+        .chain(value_requirements(arg))
+        .collect()
+}
+
+/// Gets the values of the given argument that may not be supported, such as `list-border` in
+/// `--color=dark,list-border:red`, along with the minimum version of `fzf` that supports each
+fn value_requirements(arg: &str) -> Vec<(String, FzfVersion)> {
+    let Some((option, value)) = arg.split_once('=') else {
+        return vec![];
+    };
+    let values: Vec<&str> = match option {
+        // Each element is followed by its color and attributes, such as `fg:red:bold`
+        "--color" => value
+            .split(',')
+            .map(|x| x.split(':').next().unwrap_or(x))
+            .collect(),
+        // The alternative layout is wrapped in parentheses, such as `<80(down,border-line)`
+        "--preview-window" => value.split([',', '(', ')']).collect(),
+        "--bind" => bind_values(value),
+        "--listen" if value.ends_with(".sock") => vec!["*.sock"],
+        _ => vec![],
+    };
+    VALUE_VERSIONS
+        .iter()
+        .filter(|(x, value, _)| *x == option && values.contains(value))
+        .map(|(_, value, required)| (format!("{}={}", option, value), *required))
+        .collect()
+}
+
+/// Gets the event of a `--bind` value followed by the name of each action in its chain, skipping
+/// their payloads
+///
+/// The event keeps the `:` that ends it, so it can't be mistaken for an action of the same name
+fn bind_values(bind: &str) -> Vec<&str> {
+    let Some((key, mut chain)) = bind.split_once(':') else {
+        return vec![];
+    };
+    let mut values = vec![&bind[..=key.len()]];
+    while !chain.is_empty() {
+        let mut end = chain
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(chain.len());
+        // The actions `fzf` names with a `+` or `/`, such as `toggle+up`
+        let suffixes: &[&str] = match &chain[..end] {
+            "toggle" => &["+up", "+down"],
+            name if name.ends_with("delete-char") => &["/eof"],
+            _ => &[],
+        };
+        if let Some(suffix) = suffixes.iter().find(|x| chain[end..].starts_with(*x)) {
+            end += suffix.len();
+        }
+        values.push(&chain[..end]);
+
+        let rest = &chain[end..];
+        let Some(next) = rest.chars().next() else {
+            break;
+        };
+        chain = match action::closing_delimiter(next) {
+            // The payload doesn't contain its closing delimiter, so the first one ends it
+            Some(close) => match rest[1..].find(close) {
+                Some(i) => rest[1 + i + close.len_utf8()..].trim_start_matches('+'),
+                None => break,
+            },
+            None if next == '+' => &rest[1..],
+            // The `action:payload` form, which runs to the end of the chain
+            None => break,
+        };
+    }
+    values
+}
+
+/// Checks the given arguments against the installed version of `fzf`, following the policy
+///
+/// # Returns
+///
+/// The arguments to pass to `fzf`, and a warning for each one that was dropped
+pub(crate) fn check_args(
+    args: Vec<String>,
+    version: Option<FzfVersion>,
+    policy: VersionPolicy,
+) -> Result<(Vec<String>, Vec<String>), FzfError> {
+    let Some(found) = version else {
+        return Ok((args, vec![]));
+    };
+
+    let mut kept = vec![];
+    let mut warnings = vec![];
+    for arg in args {
+        let unsupported = requirements(&arg)
+            .into_iter()
+            .find(|(_, required)| *required > found);
+        match unsupported {
+            Some((option, required)) => match policy {
+                VersionPolicy::Ignore => kept.push(arg),
+                VersionPolicy::Warn => warnings.push(format!(
+                    "{} requires fzf {}, but {} is installed, so it was not passed",
+                    option, required, found
+                )),
+                VersionPolicy::Error => {
+                    return Err(FzfError::UnsupportedOption {
+                        option,
+                        required,
+                        found,
+                    })
+                }
+            },
+            None => kept.push(arg),
+        }
+    }
+    Ok((kept, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_output_is_parsed() {
+        let parse = |x: &str| x.parse::<FzfVersion>().ok();
+
+        assert_eq!(parse("0.44.1 (d7d2ac3)\n"), Some(FzfVersion::new(0, 44, 1)));
+        assert_eq!(parse("0.40.0 (brew)"), Some(FzfVersion::new(0, 40, 0)));
+        assert_eq!(parse("0.29"), Some(FzfVersion::new(0, 29, 0)));
+        assert_eq!(parse("0.18.0-devel"), Some(FzfVersion::new(0, 18, 0)));
+        assert_eq!(parse("not fzf"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn unsupported_options_follow_the_policy() {
+        let args = || vec!["--scheme=path".to_string(), "--cycle".to_string()];
+        let old = Some(FzfVersion::new(0, 30, 0));
+
+        let (kept, warnings) = check_args(args(), old, VersionPolicy::Warn).unwrap();
+        assert_eq!(kept, vec!["--cycle"]);
+        assert_eq!(warnings.len(), 1);
+
+        assert!(matches!(
+            check_args(args(), old, VersionPolicy::Error),
+            Err(FzfError::UnsupportedOption { option, .. }) if option == "--scheme"
+        ));
+
+        let height = vec!["--height=~40%".to_string(), "--height=40%".to_string()];
+        let (kept, _) = check_args(height, old, VersionPolicy::Warn).unwrap();
+        assert_eq!(kept, vec!["--height=40%"]);
+
+        let borders = ["--border=rounded", "--border=bold", "--border=thinblock"].map(String::from);
+        let (kept, _) = check_args(borders.to_vec(), old, VersionPolicy::Warn).unwrap();
+        assert_eq!(kept, vec!["--border=rounded", "--border=bold"]);
+
+        let colors = ["--color=dark,fg:red", "--color=dark,list-border:red:bold"].map(String::from);
+        let (kept, warnings) = check_args(colors.to_vec(), old, VersionPolicy::Warn).unwrap();
+        assert_eq!(kept, vec!["--color=dark,fg:red"]);
+        assert!(warnings[0].starts_with("--color=list-border requires fzf 0.58.0"));
+
+        let socket = ["--listen=6266", "--listen=/tmp/fzf.sock"].map(String::from);
+        let new = Some(FzfVersion::new(0, 40, 0));
+        let (kept, _) = check_args(socket.to_vec(), new, VersionPolicy::Warn).unwrap();
+        assert_eq!(kept, vec!["--listen=6266"]);
+
+        assert_eq!(
+            check_args(args(), new, VersionPolicy::Error).unwrap().0,
+            args()
+        );
+        assert_eq!(
+            check_args(args(), None, VersionPolicy::Error).unwrap().0,
+            args()
+        );
+    }
+
+    #[test]
+    fn bind_events_and_actions_are_found() {
+        assert_eq!(
+            bind_values("start:execute-silent(touch '/tmp/a+b')+toggle+up+first"),
+            vec!["start:", "execute-silent", "toggle+up", "first"]
+        );
+        assert_eq!(
+            bind_values("ctrl-h:backward-delete-char/eof+change-query[)]+become:vim {+}"),
+            vec![
+                "ctrl-h:",
+                "backward-delete-char/eof",
+                "change-query",
+                "become"
+            ]
+        );
+        assert_eq!(
+            requirements("--bind=enter:accept-or-print-query"),
+            vec![(
+                "--bind=accept-or-print-query".to_string(),
+                FzfVersion::new(0, 41, 0)
+            )]
+        );
+        assert!(requirements("--bind=jump:accept+jump").len() == 1);
+        assert_eq!(
+            requirements("--preview-window=right,<80(down,border-line)")[0].0,
+            "--preview-window=border-line"
+        );
+    }
+}